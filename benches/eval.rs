@@ -0,0 +1,80 @@
+//! Benchmarks for evaluating Monkey programs.
+//!
+//! Run with `cargo bench`. Criterion saves the results of each run under
+//! `target/criterion`, and reports the change from the previous run, so
+//! regressions can be spotted by running the benchmarks before and after a
+//! change.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use monkey_rs::eval::{self, environment::Env};
+use monkey_rs::parser::{self, ast::Node};
+
+/// Recursive fibonacci, dominated by function application and integer
+/// arithmetic.
+const FIBONACCI: &str = "
+let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };
+";
+
+/// Builds an array with `push`, then maps and sums over it, dominated by array
+/// allocation and indexing.
+const ARRAYS: &str = "
+let build = fn(n) {
+  let iter = fn(i, acc) { if (i == n) { acc } else { iter(i + 1, push(acc, i)) } };
+  iter(0, [])
+};
+let map = fn(arr, f) {
+  let iter = fn(i, acc) {
+    if (i == len(arr)) { acc } else { iter(i + 1, push(acc, f(arr[i]))) }
+  };
+  iter(0, [])
+};
+let sum = fn(arr) {
+  let iter = fn(i, acc) { if (i == len(arr)) { acc } else { iter(i + 1, acc + arr[i]) } };
+  iter(0, 0)
+};
+sum(map(build(500), fn(x) { x * 2 }));
+";
+
+/// Parse the source code, panicking if it is invalid.
+fn parse(src: &str) -> Node {
+    parser::parse(src).expect("benchmark program should parse")
+}
+
+/// Benchmark evaluating the program in a fresh environment. Parsing and
+/// cloning the AST are excluded from the measurement.
+fn bench_program(c: &mut Criterion, name: &str, src: &str) {
+    let program = parse(src);
+    c.bench_function(name, |b| {
+        b.iter_batched(
+            || {
+                let env: Env = Rc::new(RefCell::new(Default::default()));
+                (program.clone(), env)
+            },
+            |(program, env)| eval::eval(program, &env).expect("benchmark program should run"),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn fibonacci(c: &mut Criterion) {
+    bench_program(c, "fib(20)", &format!("{FIBONACCI}fib(20);"));
+}
+
+fn fibonacci_30(c: &mut Criterion) {
+    bench_program(c, "fib(30)", &format!("{FIBONACCI}fib(30);"));
+}
+
+fn arrays(c: &mut Criterion) {
+    bench_program(c, "arrays(500)", ARRAYS);
+}
+
+criterion_group!(benches, fibonacci, arrays);
+criterion_group! {
+    name = slow_benches;
+    config = Criterion::default().sample_size(10);
+    targets = fibonacci_30
+}
+criterion_main!(benches, slow_benches);
@@ -3,76 +3,166 @@
 */
 use clap::Parser;
 use monkey_rs::{
-    eval::{self, environment::Env},
-    parser, repl,
+    eval::context::Context, eval::object::Object, lexer, parser, repl, token, Error, Interpreter,
 };
 use rustyline::Result;
-use std::cell::RefCell;
-use std::path::PathBuf;
-use std::rc::Rc;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
 
-/// Runs and evaluates the Monkey source file (`*.monkey`), if provided, else
-/// starts a Monkey REPL session to run Monkey code.
+/// Runs and evaluates the Monkey source files (`*.monkey`), if provided, or the
+/// program piped through STDIN, else starts a Monkey REPL session to run Monkey
+/// code.
 #[derive(Parser, Debug)]
 struct Args {
-    /// Path to a Monkey source file to execute (must have `.monkey` extension).
-    #[arg(value_name = "FILE")]
-    input: Option<PathBuf>,
+    /// Paths to Monkey source files to execute in order, sharing a single
+    /// environment (must have `.monkey` extension), followed by any arguments
+    /// to pass to the program, which it can read with `args()`. An explicit
+    /// `--` ends the source files, so that every argument after it is passed
+    /// to the program. Options must precede the source files.
+    #[arg(
+        value_name = "FILE",
+        trailing_var_arg = true,
+        allow_hyphen_values = true
+    )]
+    input: Vec<PathBuf>,
+
+    /// Print the token stream of the source files, one token per line preceded
+    /// by its `line:column` position, instead of evaluating them.
+    #[arg(long, requires = "input")]
+    tokens: bool,
+
+    /// Print the parsed AST of the source files as indented source code instead
+    /// of evaluating them.
+    #[arg(long, requires = "input", conflicts_with = "tokens")]
+    ast: bool,
+
+    /// Check that the source files parse without evaluating them, printing
+    /// nothing on success and exiting with a non-zero status on parse errors.
+    #[arg(long, requires = "input", conflicts_with_all = ["tokens", "ast"])]
+    check: bool,
 }
 
 /// Runs the Monkey interpreter, either executing a source file or starting a
 /// REPL session.
 ///
 /// This function parses command-line arguments to determine whether to process
-/// a `.monkey` file or launch an interactive REPL session. If a file is
-/// provided, it validates the file extension, reads the file contents, parses
-/// and evaluates the Monkey code, and outputs the result. If no file is
-/// provided, it starts the REPL for interactive code execution.
+/// `.monkey` files or launch an interactive REPL session. If files are
+/// provided, it validates each file extension, reads the file contents, parses
+/// and evaluates the Monkey code of each file in order against a single shared
+/// environment, and outputs the result of the last file. If no file is
+/// provided and STDIN is piped rather than a terminal, the whole of STDIN is
+/// evaluated as a program instead. Otherwise, it starts the REPL for
+/// interactive code execution.
 ///
 /// # Returns
 ///
 /// - `Ok(())` on successful execution or if an error is handled
-/// gracefully (e.g., invalid file extension).
+///   gracefully (e.g., invalid file extension).
 ///
 /// - `Err(e)` if file reading, parsing, or REPL operations encounter an
-/// unrecoverable error.
+///   unrecoverable error.
 ///
 /// # Errors
 ///
 /// - Returns an error if the input file cannot be read (e.g., file not found).
 ///
 /// - Returns an error if the REPL encounters an issue (e.g., interrupted
-/// input).
+///   input).
 ///
 /// - Prints an error message and exits gracefully if the file lacks a `.monkey`
-/// extension or has no extension.
+///   extension or has no extension.
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    if !args.input.is_empty() {
+        // The source files run up to an explicit `--`, or else up to the
+        // first argument that is not a `.monkey` file, and the remaining
+        // arguments are for the program
+        let separator = args.input.iter().position(|arg| arg == "--");
+        let files = separator.unwrap_or_else(|| {
+            args.input
+                .iter()
+                .position(|arg| !is_source_file(arg))
+                .unwrap_or(args.input.len())
+                .max(1)
+        });
+        let program_args = args
+            .input
+            .split_off(files)
+            .iter()
+            .skip(usize::from(separator.is_some()))
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
 
-    if let Some(file) = args.input {
-        // Check file extension, if it exists
-        if let Some(ext) = file.extension() {
-            if ext.to_string_lossy().to_lowercase() != "monkey" {
-                eprint!("Error: File must have a .monkey extension");
+        let mut sources = Vec::new();
+        for file in args.input {
+            // Check file extension, if it exists
+            if let Some(ext) = file.extension() {
+                if ext.to_string_lossy().to_lowercase() != "monkey" {
+                    eprint!("Error: File must have a .monkey extension");
+                    return Ok(());
+                }
+            } else {
+                eprintln!("Error: File has no extension");
                 return Ok(());
             }
-        } else {
-            eprintln!("Error: File has no extension");
+
+            let input = std::fs::read_to_string(&file)?;
+            sources.push((Some(file), input));
+        }
+
+        // Dump the token stream without parsing or evaluating
+        if args.tokens {
+            for (_, input) in &sources {
+                let mut lexer = lexer::Lexer::new(input);
+                loop {
+                    let token = lexer.next_token();
+                    println!("{} {:?}", lexer.token_position(), token);
+                    if token == token::Token::Eof {
+                        break;
+                    }
+                }
+            }
+            return Ok(());
+        }
+
+        // Check the syntax of every file without evaluating
+        if args.check {
+            let mut valid = true;
+            for (file, input) in &sources {
+                if let Err(e) = parser::parse(input) {
+                    eprintln!("{}: {}", source_name(file.as_deref()), e.render(input));
+                    valid = false;
+                }
+            }
+            if !valid {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+
+        // Dump the parsed AST without evaluating
+        if args.ast {
+            for (file, input) in &sources {
+                match parser::parse(input) {
+                    Ok(program) => println!("{}", program.pretty(0)),
+                    Err(e) => {
+                        eprintln!("{}: {}", source_name(file.as_deref()), e.render(input));
+                        std::process::exit(1);
+                    }
+                }
+            }
             return Ok(());
         }
 
         // Run file contents
-        let input = std::fs::read_to_string(file)?;
-        let env: Env = Rc::new(RefCell::new(Default::default()));
-
-        // NOTE: only `puts(...)` statements and the last evaluated statement
-        // will be emitted to STDOUT
-        match parser::parse(&input) {
-            Ok(program) => match eval::eval(program, &Rc::clone(&env)) {
-                Ok(evaluated) => println!("{}", evaluated),
-                Err(e) => eprintln!("{}", e),
-            },
-            Err(e) => eprintln!("{}", e),
+        run_programs(&sources, program_args);
+    } else if !io::stdin().is_terminal() {
+        // Run the program piped through STDIN
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        if !input.trim().is_empty() {
+            run_programs(&[(None, input)], Vec::new());
         }
     } else {
         // Start interactive REPL session
@@ -81,3 +171,56 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Evaluates Monkey programs, each read from the given file or STDIN if
+/// `None`, in order against a single shared environment and prints the result
+/// of the last one, passing the given arguments to the programs. Evaluation
+/// stops at the first program that fails, reporting its source alongside the
+/// error.
+///
+/// NOTE: only `puts(...)` statements and the last evaluated statement will be
+/// emitted to STDOUT, and the last evaluated statement only if it is not
+/// `null`. Errors are reported on STDERR.
+fn run_programs(sources: &[(Option<PathBuf>, String)], args: Vec<String>) {
+    let mut interpreter = Interpreter::with_context(Context::default().with_args(args));
+    let mut result = None;
+    for (file, input) in sources {
+        let evaluated = match file {
+            Some(file) => interpreter.eval_source(input, file),
+            None => interpreter.eval_str(input),
+        };
+        match evaluated {
+            Ok(evaluated) => result = Some(evaluated),
+            // Show parse errors against the offending lines of the source
+            Err(Error::Parse(e)) => {
+                eprintln!(
+                    "{}: parse error: {}",
+                    source_name(file.as_deref()),
+                    e.render(input)
+                );
+                return;
+            }
+            Err(e) => {
+                eprintln!("{}: {}", source_name(file.as_deref()), e);
+                return;
+            }
+        }
+    }
+    // Programs that end in a statement evaluating to `null`, e.g., a
+    // `puts(...)` call, should not print a trailing `null`
+    match result.as_deref() {
+        Some(Object::Null) | None => {}
+        Some(evaluated) => println!("{}", evaluated),
+    }
+}
+
+/// Returns whether the path has a `.monkey` extension.
+fn is_source_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.to_string_lossy().to_lowercase() == "monkey")
+}
+
+/// Returns the name to report a source by: its file path, or `<stdin>`.
+fn source_name(file: Option<&Path>) -> String {
+    file.map_or_else(|| "<stdin>".to_string(), |file| file.display().to_string())
+}
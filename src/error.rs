@@ -0,0 +1,52 @@
+/*!
+# Error
+
+Defines the crate-level `Error` type, which unifies the errors that can occur
+while running Monkey source code.
+*/
+use std::fmt;
+
+use crate::eval::error::EvaluationError;
+use crate::parser::error::ParserError;
+
+/// An error encountered while parsing or evaluating Monkey source code.
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// An error encountered while parsing the source code.
+    Parse(ParserError),
+    /// An error encountered while evaluating the parsed program.
+    Eval(EvaluationError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(err) => write!(f, "parse error: {}", err),
+            Error::Eval(err) => match err.position() {
+                Some(position) => write!(f, "evaluation error at line {}: {}", position, err),
+                None => write!(f, "evaluation error: {}", err),
+            },
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(err) => Some(err),
+            Error::Eval(err) => Some(err),
+        }
+    }
+}
+
+impl From<ParserError> for Error {
+    fn from(err: ParserError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<EvaluationError> for Error {
+    fn from(err: EvaluationError) -> Self {
+        Error::Eval(err)
+    }
+}
@@ -6,29 +6,121 @@ interpreting the parsed AST representation of the source code "on the fly."
 */
 /* Modules */
 pub(crate) mod builtin;
+pub mod context;
 pub mod environment;
 pub mod error;
-pub(crate) mod object;
+pub mod object;
+pub(crate) mod random;
 
 /* Re-exports */
 pub use builtin::Builtin;
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{borrow::Cow, cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::{parser::ast, token};
+use crate::{parser::ast, symbol::Symbol, token, token::Position};
+
+/// The maximum length of the result of a repetition, in bytes for a string,
+/// e.g., `"ab" * 3`, or elements for an array, e.g., `[0] * 3`, which keeps a
+/// huge repeat count from exhausting memory.
+const MAX_REPEAT_LEN: usize = 1 << 24;
 
 /// Evaluate a parsed Monkey AST node and return its corresponding object
-/// representation.
+/// representation. Output from builtins such as `puts` is written to STDOUT.
 pub fn eval(
     node: ast::Node,
     env: &environment::Env,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    eval_with_context(node, env, &mut context::Context::default())
+}
+
+/// Evaluate a parsed Monkey AST node within the given evaluation context and
+/// return its corresponding object representation. Output from builtins such
+/// as `puts` is written to the context's output writer.
+pub fn eval_with_context(
+    node: ast::Node,
+    env: &environment::Env,
+    ctx: &mut context::Context,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
     match node {
         /* Statements */
-        ast::Node::Program(program) => eval_program(&program, env),
-        ast::Node::Stmt(statement) => eval_statement(&statement, env),
+        ast::Node::Program(program) => eval_program(&program, env, ctx),
+        ast::Node::Stmt(statement) => eval_statement(&statement, env, ctx),
         /* Expressions */
-        ast::Node::Expr(expression) => eval_expression(&expression, env),
+        ast::Node::Expr(expression) => eval_expression(&expression, env, ctx),
+    }
+}
+
+/// Evaluate a parsed Monkey AST node, failing with an
+/// [`ErrorKind::StepLimit`](error::ErrorKind::StepLimit) error if evaluation
+/// takes more than the given number of steps, i.e., statements and expressions
+/// evaluated. Output from builtins such as `puts` is written to STDOUT.
+pub fn eval_with_limit(
+    node: ast::Node,
+    env: &environment::Env,
+    limit: u64,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    eval_with_context(
+        node,
+        env,
+        &mut context::Context::default().with_step_limit(limit),
+    )
+}
+
+/// Evaluate a parsed Monkey AST node and return the value of every top-level
+/// statement in order, e.g., to display the result of each statement of a
+/// program. Evaluation stops at the first error or top-level `return`. Output
+/// from builtins such as `puts` is written to STDOUT.
+pub fn eval_all(
+    node: ast::Node,
+    env: &environment::Env,
+) -> Result<Vec<Rc<object::Object>>, error::EvaluationError> {
+    eval_all_with_context(node, env, &mut context::Context::default())
+}
+
+/// Evaluate a parsed Monkey AST node within the given evaluation context and
+/// return the value of every top-level statement in order, as [`eval_all`]
+/// does. Output from builtins such as `puts` is written to the context's
+/// output writer.
+pub fn eval_all_with_context(
+    node: ast::Node,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Vec<Rc<object::Object>>, error::EvaluationError> {
+    match node {
+        ast::Node::Program(program) => {
+            let mut results = Vec::with_capacity(program.len());
+
+            for stmt in &program {
+                let result = eval_statement(stmt, env, ctx)?;
+                let is_return = is_signal(&result);
+                results.push(unwrap_return_value(result)?);
+
+                // Stop early if encounter a return statement
+                if is_return {
+                    break;
+                }
+            }
+
+            Ok(results)
+        }
+        node => Ok(vec![eval_with_context(node, env, ctx)?]),
+    }
+}
+
+/// Returns whether the condition of an `if` holds, i.e., whether the
+/// condition is truthy, or, if the context requires strict booleans, whether
+/// it is `true`, failing if it is not a boolean.
+fn eval_condition(
+    condition: &object::Object,
+    ctx: &context::Context,
+) -> Result<bool, error::EvaluationError> {
+    match condition {
+        object::Object::Boolean(condition) => Ok(*condition),
+        other if ctx.strict_booleans() => Err(error::EvaluationError::type_mismatch(format!(
+            "condition must be a boolean, got {}",
+            other.type_name()
+        ))),
+        other => Ok(is_truthy(other)),
     }
 }
 
@@ -42,92 +134,299 @@ fn is_truthy(object: &object::Object) -> bool {
 
 /// Evaluate a parsed Monkey AST expression node and return its corresponding
 /// object representation.
+///
+/// A frame of this function is on the stack for every level of nesting of
+/// the program being evaluated, so expressions that evaluate operands are
+/// evaluated by separate functions to keep its frame small.
 fn eval_expression(
     expression: &ast::Expression,
     env: &environment::Env,
+    ctx: &mut context::Context,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
+    ctx.step()?;
     match expression {
-        ast::Expression::Identifier(ident) => eval_identifier(ident, env),
-        ast::Expression::Lit(ast::Literal::Integer(value)) => {
-            Ok(Rc::new(object::Object::Integer(*value as i64)))
+        ast::Expression::Identifier(ident, position) => {
+            eval_identifier(*ident, env).map_err(|e| e.at(*position))
         }
-        ast::Expression::Lit(ast::Literal::Boolean(value)) => {
-            Ok(Rc::new(object::Object::Boolean(*value)))
+        ast::Expression::Lit(literal) => eval_literal(literal, env, ctx),
+        ast::Expression::Prefix(operator, right, position) => {
+            eval_prefix(operator, right, *position, env, ctx)
         }
-        ast::Expression::Lit(ast::Literal::String(value)) => {
-            Ok(Rc::new(object::Object::String(value.clone())))
+        ast::Expression::Infix(operator, left, right, position) => {
+            eval_infix(operator, left, right, *position, env, ctx)
         }
-        ast::Expression::Lit(ast::Literal::Array(arr)) => {
-            let list = eval_expressions(arr, &Rc::clone(env))?;
-            Ok(Rc::new(object::Object::Array(list)))
+        ast::Expression::If(condition, consequence, alternative) => {
+            eval_if(condition, consequence, alternative.as_deref(), env, ctx)
         }
-        ast::Expression::Lit(ast::Literal::Hash(entries)) => {
-            let hash = eval_hash_literal(entries, &Rc::clone(env))?;
-            Ok(Rc::new(object::Object::Hash(hash)))
+        ast::Expression::Fn(params, body) => Ok(eval_function_literal(params, body, env)),
+        ast::Expression::Call(func, args, position) => eval_call(func, args, *position, env, ctx),
+        ast::Expression::Index(left, index, position) => {
+            eval_index(left, index, *position, env, ctx)
         }
-        ast::Expression::Prefix(operator, expression) => {
-            let right = eval_expression(expression, env)?;
-            eval_prefix_expression(operator, &right)
+        ast::Expression::Slice(left, start, end, position) => {
+            eval_slice(left, start.as_deref(), end.as_deref(), *position, env, ctx)
         }
-        ast::Expression::Infix(operator, left, right) => {
-            let left = eval_expression(left, &Rc::clone(env))?;
-            let right = eval_expression(right, env)?;
-            eval_infix_expression(operator, &left, &right)
+        ast::Expression::Switch(subject, arms, position) => {
+            eval_switch_expression(subject, arms, *position, env, ctx)
         }
-        ast::Expression::If(condition, consequence, alternative) => {
-            let condition = eval_expression(condition, &Rc::clone(env))?;
-
-            if is_truthy(&condition) {
-                eval_block_statement(consequence, env)
-            } else {
-                match alternative {
-                    Some(alt) => eval_block_statement(alt, env),
-                    None => Ok(Rc::new(object::Object::Null)),
-                }
-            }
+        ast::Expression::Do(body) => eval_scoped_block(body, env, ctx),
+        ast::Expression::For(ident, iterable, body, position) => {
+            eval_for_expression(*ident, iterable, *position, body, env, ctx)
         }
-        ast::Expression::Fn(params, body) => Ok(Rc::new(object::Object::Function(
-            params.clone(),
-            body.clone(),
-            Rc::clone(env),
-        ))),
-        ast::Expression::Call(func, args) => {
-            let func = eval_expression(func, &Rc::clone(env))?;
-            let args = eval_expressions(args, env)?;
-            apply_function(&func, &args)
+    }
+}
+
+/// Evaluate a literal expression, evaluating the elements of an array or the
+/// entries of a hash from left-to-right.
+fn eval_literal(
+    literal: &ast::Literal,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let object = match literal {
+        ast::Literal::Integer(value) => object::Object::Integer(*value),
+        ast::Literal::Boolean(value) => object::Object::Boolean(*value),
+        ast::Literal::Null => object::Object::Null,
+        ast::Literal::String(value) => object::Object::String(value.clone()),
+        ast::Literal::Array(arr) => object::Object::Array(eval_expressions(arr, env, ctx)?),
+        ast::Literal::Hash(entries) => object::Object::Hash(eval_hash_literal(entries, env, ctx)?),
+    };
+    Ok(Rc::new(object))
+}
+
+/// Evaluate a function literal into a function closing over the given
+/// environment.
+fn eval_function_literal(
+    params: &[ast::Parameter],
+    body: &[ast::Statement],
+    env: &environment::Env,
+) -> Rc<object::Object> {
+    Rc::new(object::Object::Function(
+        params.to_vec(),
+        body.to_vec(),
+        Rc::clone(env),
+    ))
+}
+
+/// Evaluate the operand of a prefix expression, then apply the operator to
+/// it, reporting an error at the given position of the expression.
+fn eval_prefix(
+    operator: &token::Token,
+    right: &ast::Expression,
+    position: Position,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let right = eval_expression(right, env, ctx)?;
+    eval_prefix_expression(operator, &right).map_err(|e| e.at(position))
+}
+
+/// Evaluate the operands of an infix expression from left-to-right, then
+/// apply the operator to them, reporting an error at the given position of
+/// the expression.
+fn eval_infix(
+    operator: &token::Token,
+    left: &ast::Expression,
+    right: &ast::Expression,
+    position: Position,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let left = eval_expression(left, env, ctx)?;
+    let right = eval_expression(right, env, ctx)?;
+    eval_infix_expression(operator, &left, &right).map_err(|e| e.at(position))
+}
+
+/// Evaluate the condition of an if-expression, then the block of the branch
+/// it selects, or `null` if there is no such branch.
+fn eval_if(
+    condition: &ast::Expression,
+    consequence: &[ast::Statement],
+    alternative: Option<&[ast::Statement]>,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let condition = eval_expression(condition, env, ctx)?;
+
+    if eval_condition(&condition, ctx)? {
+        eval_scoped_block(consequence, env, ctx)
+    } else {
+        match alternative {
+            Some(alt) => eval_scoped_block(alt, env, ctx),
+            None => Ok(Rc::new(object::Object::Null)),
         }
-        ast::Expression::Index(left, index) => {
-            // Evaluate both expressions first before evaluating indexing.
-            let left_expr = eval_expression(left, &Rc::clone(env))?;
-            let index_expr = eval_expression(index, &Rc::clone(env))?;
-            eval_index_expression(&left_expr, &index_expr)
+    }
+}
+
+/// Evaluate the function and arguments of a call expression, then apply the
+/// function, reporting an error at the given position of the call.
+fn eval_call(
+    func: &ast::Expression,
+    args: &[ast::Argument],
+    position: Position,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let func = eval_expression(func, env, ctx)?;
+    let (args, kwargs) = eval_arguments(args, env, ctx)?;
+    // Errors raised within the body of a function keep the position of the
+    // expression that raised them
+    apply_function(&func, &args, &kwargs, env, ctx).map_err(|e| e.at(position))
+}
+
+/// Evaluate the indexed and index expressions of an index expression, then
+/// index, reporting an error at the given position of the expression.
+fn eval_index(
+    left: &ast::Expression,
+    index: &ast::Expression,
+    position: Position,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    // Evaluate both expressions first before evaluating indexing.
+    let left = eval_expression(left, env, ctx)?;
+    let index = eval_expression(index, env, ctx)?;
+    eval_index_expression(&left, &index).map_err(|e| e.at(position))
+}
+
+/// Evaluate the sliced expression and bounds of a slice expression, then
+/// slice, reporting an error at the given position of the expression.
+fn eval_slice(
+    left: &ast::Expression,
+    start: Option<&ast::Expression>,
+    end: Option<&ast::Expression>,
+    position: Position,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let left = eval_expression(left, env, ctx)?;
+    let start = eval_slice_bound(start, env, ctx).map_err(|e| e.at(position))?;
+    let end = eval_slice_bound(end, env, ctx).map_err(|e| e.at(position))?;
+    eval_slice_expression(&left, start, end).map_err(|e| e.at(position))
+}
+
+/// Evaluate the block of a for-in expression once for each element of an
+/// array, or each `[key, value]` entry of a hash, bound to the identifier in a
+/// new environment enclosed by the given one. Evaluates to `null`, unless the
+/// block returns. An error iterating over any other object is reported at the
+/// given position of the iterated expression.
+fn eval_for_expression(
+    ident: Symbol,
+    iterable: &ast::Expression,
+    position: Position,
+    body: &[ast::Statement],
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let iterable = eval_expression(iterable, env, ctx)?;
+    let elements = match &*iterable {
+        object::Object::Array(elements) => elements.clone(),
+        object::Object::Hash(hash) => builtin::entries(hash),
+        other => {
+            return Err(error::EvaluationError::type_mismatch(format!(
+                "cannot iterate over {}",
+                other.type_name()
+            ))
+            .at(position))
+        }
+    };
+
+    for element in elements {
+        let env = enclose(env, 1, ctx)?;
+        env.borrow_mut().set(ident, element);
+
+        let result = eval_block_statement(body, &env, ctx)?;
+        match *result {
+            object::Object::Break => break,
+            object::Object::ReturnValue(_) => return Ok(result),
+            _ => continue,
+        }
+    }
+
+    Ok(Rc::new(object::Object::Null))
+}
+
+/// Evaluate the subject of a switch expression, then the value of the first
+/// arm whose pattern matches the subject, i.e., is of the same type and equal
+/// to it. Evaluates to `null` if no arm matches. Errors not raised at a more
+/// deeply nested expression are reported at the subject.
+fn eval_switch_expression(
+    subject: &ast::Expression,
+    arms: &[(ast::Pattern, ast::Expression)],
+    position: token::Position,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let subject = eval_expression(subject, env, ctx).map_err(|e| e.at(position))?;
+    match match_switch_arm(&subject, arms, env, ctx).map_err(|e| e.at(position))? {
+        Some(value) => eval_expression(value, env, ctx).map_err(|e| e.at(position)),
+        None => Ok(Rc::new(object::Object::Null)),
+    }
+}
+
+/// Returns the value expression of the first arm of a switch expression whose
+/// pattern matches the subject, if any. A pattern of a different type than the
+/// subject does not match it, rather than failing as `==` would.
+fn match_switch_arm<'a>(
+    subject: &Rc<object::Object>,
+    arms: &'a [(ast::Pattern, ast::Expression)],
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Option<&'a ast::Expression>, error::EvaluationError> {
+    for (pattern, value) in arms {
+        let matches = match pattern {
+            ast::Pattern::Wildcard => true,
+            ast::Pattern::Value(pattern) => {
+                let pattern = eval_expression(pattern, env, ctx)?;
+                // Functions close over environments, so there is no meaningful
+                // sense in which two of them are equal
+                if builtin::contains_function(subject) || builtin::contains_function(&pattern) {
+                    return Err(error::EvaluationError::type_mismatch(
+                        "cannot compare functions".to_string(),
+                    ));
+                }
+                **subject == *pattern
+            }
+        };
+        if matches {
+            return Ok(Some(value));
         }
     }
+
+    Ok(None)
 }
 
 /// Evaluate the hash literal expression with the given (key, value) expression
-/// entries.
+/// entries. Each key may appear at most once.
 fn eval_hash_literal(
     entries: &[(ast::Expression, ast::Expression)],
     env: &environment::Env,
+    ctx: &mut context::Context,
 ) -> Result<HashMap<Rc<object::HashableObject>, Rc<object::Object>>, error::EvaluationError> {
     let mut hash = HashMap::new();
 
     for (key_expr, value_expr) in entries {
-        let key_obj = eval_expression(key_expr, env)?;
+        let key_obj = eval_expression(key_expr, env, ctx)?;
 
         // Verify that key object is hashable
         let hash_key = match key_obj.as_hashable() {
             Some(k) => Rc::new(k),
             None => {
-                return Err(error::EvaluationError::new(format!(
+                return Err(error::EvaluationError::type_mismatch(format!(
                     "unusable as hash key: {}",
                     key_obj
                 )))
             }
         };
 
-        let value_obj = eval_expression(value_expr, env)?;
+        if hash.contains_key(&hash_key) {
+            return Err(error::EvaluationError::duplicate_key(format!(
+                "duplicate hash key: {}",
+                hash_key
+            )));
+        }
+
+        let value_obj = eval_expression(value_expr, env, ctx)?;
         hash.insert(hash_key, value_obj);
     }
 
@@ -143,8 +442,11 @@ fn eval_index_expression(
         (object::Object::Array(arr), object::Object::Integer(idx)) => {
             eval_array_index_expression(arr, *idx)
         }
+        (object::Object::String(str), object::Object::Integer(idx)) => {
+            eval_string_index_expression(str, *idx)
+        }
         (object::Object::Hash(hash), key) => eval_hash_index_expression(hash, key),
-        _ => Err(error::EvaluationError::new(format!(
+        _ => Err(error::EvaluationError::index_error(format!(
             "index operator not supported: {}",
             index_expr
         ))),
@@ -160,7 +462,7 @@ fn eval_hash_index_expression(
     let hash_key = match key.as_hashable() {
         Some(k) => &Rc::new(k),
         None => {
-            return Err(error::EvaluationError::new(format!(
+            return Err(error::EvaluationError::type_mismatch(format!(
                 "unusable as hash key: {}",
                 key
             )))
@@ -173,81 +475,366 @@ fn eval_hash_index_expression(
     }
 }
 
-/// Evaluate the array index expression from the given array object and index
+/// Resolve an index into a collection of the given length. Negative indices
+/// count backwards from the end of the collection, e.g., `-1` is the last
+/// element. Returns `None` if the index is out of bounds.
+fn resolve_index(idx: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let idx = if idx < 0 { idx + len } else { idx };
+
+    if idx < 0 || idx >= len {
+        None
+    } else {
+        Some(idx as usize)
+    }
+}
+
+/// Evaluate the array index expression from the given array object and index.
 fn eval_array_index_expression(
     arr: &[Rc<object::Object>],
     idx: i64,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
-    let max = (arr.len() as i64) - 1;
+    match resolve_index(idx, arr.len()) {
+        Some(idx) => Ok(Rc::clone(&arr[idx])),
+        None => Ok(Rc::new(object::Object::Null)),
+    }
+}
 
-    if idx < 0 || idx > max {
-        Ok(Rc::new(object::Object::Null))
-    } else {
-        let obj = arr.get(idx as usize).expect("Index out of bounds");
-        Ok(Rc::clone(obj))
+/// Evaluate the string index expression from the given string and index,
+/// returning the single-character string at that character (not byte) offset.
+fn eval_string_index_expression(
+    str: &str,
+    idx: i64,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let ch = resolve_index(idx, str.chars().count()).and_then(|idx| str.chars().nth(idx));
+
+    match ch {
+        Some(ch) => Ok(Rc::new(object::Object::String(ch.to_string()))),
+        None => Ok(Rc::new(object::Object::Null)),
+    }
+}
+
+/// Evaluate the optional bound of a slice expression, which must evaluate to
+/// an integer if present.
+fn eval_slice_bound(
+    bound: Option<&ast::Expression>,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Option<i64>, error::EvaluationError> {
+    let bound = match bound {
+        Some(bound) => eval_expression(bound, env, ctx)?,
+        None => return Ok(None),
+    };
+
+    match *bound {
+        object::Object::Integer(int) => Ok(Some(int)),
+        _ => Err(error::EvaluationError::type_mismatch(format!(
+            "slice index must be INTEGER, got {}",
+            bound
+        ))),
+    }
+}
+
+/// Resolve the optional bounds of a slice over a collection of the given
+/// length into a half-open `(start, end)` range. Negative bounds count
+/// backwards from the end of the collection, and out-of-range bounds are
+/// clamped to the collection, so the returned range is always valid.
+fn resolve_slice(start: Option<i64>, end: Option<i64>, len: usize) -> (usize, usize) {
+    let len = len as i64;
+    let clamp = |idx: i64| {
+        let idx = if idx < 0 { idx + len } else { idx };
+        idx.clamp(0, len) as usize
+    };
+
+    let start = start.map_or(0, clamp);
+    let end = end.map_or(len as usize, clamp);
+    (start, end.max(start))
+}
+
+/// Evaluate the slice expression over the given string or array object with
+/// the given optional start and end bounds.
+fn eval_slice_expression(
+    left_expr: &Rc<object::Object>,
+    start: Option<i64>,
+    end: Option<i64>,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    match &**left_expr {
+        object::Object::Array(arr) => {
+            let (start, end) = resolve_slice(start, end, arr.len());
+            Ok(Rc::new(object::Object::Array(arr[start..end].to_vec())))
+        }
+        object::Object::String(str) => {
+            let (start, end) = resolve_slice(start, end, str.chars().count());
+            let slice = str.chars().skip(start).take(end - start).collect();
+            Ok(Rc::new(object::Object::String(slice)))
+        }
+        _ => Err(error::EvaluationError::index_error(format!(
+            "slice operator not supported: {}",
+            left_expr
+        ))),
     }
 }
 
-/// Apply the function with the given arguments, returning an error with the
-/// function cannot be applied. The function and its arguments are evaluated
-/// within a new enclosed environment to run in isolation.
+/// Apply the function with the given positional and keyword arguments,
+/// returning an error with the function cannot be applied. The function and
+/// its arguments are evaluated within a new enclosed environment to run in
+/// isolation. Builtins are applied within the caller's environment.
+///
+/// A call the function makes to itself in tail position reuses this
+/// application rather than recursing, so self-recursive loops run in
+/// constant stack space.
 fn apply_function(
     func: &Rc<object::Object>,
     args: &[Rc<object::Object>],
+    kwargs: &[(Symbol, Rc<object::Object>)],
+    env: &environment::Env,
+    ctx: &mut context::Context,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
     match &**func {
-        object::Object::Function(params, body, env) => {
-            let mut env = environment::Environment::new_enclosed_environment(&Rc::clone(env));
-
-            // Check that the number of parameters passed matches the expected
-            // number of arguments
-            if params.len() != args.len() {
-                return Err(error::EvaluationError::new(format!(
-                    "invalid number of arguments: expected={}, got={}",
-                    params.len(),
-                    args.len()
-                )));
+        object::Object::Function(params, body, closure) => {
+            let mut args = Cow::Borrowed(args);
+            let mut kwargs = Cow::Borrowed(kwargs);
+            // The position of the latest self-call, which errors raised
+            // within its application are reported at
+            let mut position = None;
+            loop {
+                let tail = eval_function_body(params, body, closure, &args, &kwargs, ctx).map_err(
+                    |e| match position {
+                        Some(position) => e.at(position),
+                        None => e,
+                    },
+                )?;
+                let call = match tail {
+                    Tail::Value(value) => return unwrap_return_value(value),
+                    Tail::Call(call) => *call,
+                };
+                if !Rc::ptr_eq(&call.func, func) {
+                    return apply_tail_call(call, ctx);
+                }
+                args = Cow::Owned(call.args);
+                kwargs = Cow::Owned(call.kwargs);
+                position = Some(call.position);
             }
+        }
+        object::Object::Builtin(func) => apply_builtin(func, args, kwargs, env, ctx),
+        other => Err(not_a_function(other)),
+    }
+}
 
-            // Store the parameter values
-            for (i, param) in params.iter().enumerate() {
-                env.set(param, args[i].clone());
-            }
+/// Evaluate the body of a function applied to the given arguments in a new
+/// environment enclosed by the function's, leaving a call in tail position
+/// unapplied.
+fn eval_function_body(
+    params: &[ast::Parameter],
+    body: &[ast::Statement],
+    closure: &environment::Env,
+    args: &[Rc<object::Object>],
+    kwargs: &[(Symbol, Rc<object::Object>)],
+    ctx: &mut context::Context,
+) -> Result<Tail, error::EvaluationError> {
+    let env = enclose(closure, params.len(), ctx)?;
+    bind_arguments(params, args, kwargs, &env, ctx)?;
+    eval_tail_block(body, &env, ctx)
+}
+
+/// Apply a call left unapplied in tail position of a function body,
+/// reporting an error at the position of the call.
+fn apply_tail_call(
+    call: TailCall,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    apply_function(&call.func, &call.args, &call.kwargs, &call.env, ctx)
+        .map_err(|e| e.at(call.position))
+}
+
+/// Apply the builtin with the given positional and keyword arguments within
+/// the caller's environment. Builtins take no keyword arguments.
+fn apply_builtin(
+    func: &Builtin,
+    args: &[Rc<object::Object>],
+    kwargs: &[(Symbol, Rc<object::Object>)],
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    if !kwargs.is_empty() {
+        return Err(error::EvaluationError::invalid_keyword(format!(
+            "keyword arguments are not supported by builtin: {}",
+            func
+        )));
+    }
+    func.apply(args, env, ctx)
+}
+
+/// Returns the error for calling an object that is not a function.
+fn not_a_function(object: &object::Object) -> error::EvaluationError {
+    error::EvaluationError::not_a_function(format!("not a function: {}", object))
+}
+
+/// Bind the arguments of a call to the parameters of a function in the
+/// function's new environment.
+fn bind_arguments(
+    params: &[ast::Parameter],
+    args: &[Rc<object::Object>],
+    kwargs: &[(Symbol, Rc<object::Object>)],
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<(), error::EvaluationError> {
+    // Most calls pass exactly one positional argument per required
+    // parameter, which can be bound directly
+    if kwargs.is_empty()
+        && args.len() == params.len()
+        && params
+            .iter()
+            .all(|param| matches!(param, ast::Parameter::Required(_)))
+    {
+        let mut env = env.borrow_mut();
+        for (param, arg) in params.iter().zip(args) {
+            env.set(param.name(), Rc::clone(arg));
+        }
+        return Ok(());
+    }
+
+    // The number of arguments passed must be between the number of
+    // required parameters and the total number of parameters, which
+    // is unbounded for a function with a rest parameter
+    let required = params
+        .iter()
+        .filter(|param| matches!(param, ast::Parameter::Required(_)))
+        .count();
+    let variadic = matches!(params.last(), Some(ast::Parameter::Rest(_)));
+    let positional = params.len() - usize::from(variadic);
+    let wrong_arg_count = || {
+        let expected = if variadic {
+            format!("{}..", required)
+        } else if required == params.len() {
+            required.to_string()
+        } else {
+            format!("{}..{}", required, params.len())
+        };
+        error::EvaluationError::wrong_arg_count(format!(
+            "invalid number of arguments: expected={}, got={}",
+            expected,
+            args.len() + kwargs.len()
+        ))
+    };
+    if !variadic && args.len() > positional {
+        return Err(wrong_arg_count());
+    }
 
-            let evaluated = eval_block_statement(body, &Rc::new(RefCell::new(env)))?;
-            unwrap_return_value(evaluated)
+    // Bind the arguments to the parameters by position, then by name
+    let mut bound: Vec<Option<Rc<object::Object>>> =
+        (0..positional).map(|i| args.get(i).cloned()).collect();
+    for (name, val) in kwargs {
+        let Some(i) = params[..positional]
+            .iter()
+            .position(|param| param.name() == *name)
+        else {
+            return Err(error::EvaluationError::invalid_keyword(format!(
+                "unknown keyword argument: {}",
+                name
+            )));
+        };
+        if bound[i].is_some() {
+            return Err(error::EvaluationError::invalid_keyword(format!(
+                "multiple values for argument: {}",
+                name
+            )));
         }
-        object::Object::Builtin(func) => func.apply(args),
-        other => Err(error::EvaluationError::new(format!(
-            "not a function: {}",
-            other
-        ))),
+        bound[i] = Some(Rc::clone(val));
+    }
+    if params
+        .iter()
+        .zip(&bound)
+        .any(|(param, val)| matches!(param, ast::Parameter::Required(_)) && val.is_none())
+    {
+        return Err(wrong_arg_count());
+    }
+
+    // Store the parameter values, evaluating the defaults of omitted
+    // arguments in the function's environment so that they can refer
+    // to the preceding parameters, and collecting any extra arguments
+    // into the rest parameter
+    for (i, param) in params.iter().enumerate() {
+        let val = match (param, bound.get(i).cloned().flatten()) {
+            (ast::Parameter::Rest(_), _) => Rc::new(object::Object::Array(
+                args.iter().skip(positional).cloned().collect(),
+            )),
+            (_, Some(arg)) => arg,
+            (ast::Parameter::Default(_, default), None) => eval_expression(default, env, ctx)?,
+            (ast::Parameter::Required(_), None) => unreachable!("checked arity above"),
+        };
+        env.borrow_mut().set(param.name(), val);
     }
+
+    Ok(())
 }
 
 /// Unwraps the result of an environment, which prevents the bubbling up of the
 /// return. This is necessary so that only the evaluation of the last-called
-/// function's body is stopped.
+/// function's body is stopped. A `break` or `continue` that reaches a function
+/// or program boundary was used outside of a loop, and is an error.
 fn unwrap_return_value(
     object: Rc<object::Object>,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
-    if let object::Object::ReturnValue(val) = &*object {
-        Ok(Rc::clone(val))
-    } else {
-        Ok(object)
+    match &*object {
+        object::Object::ReturnValue(val) => Ok(Rc::clone(val)),
+        object::Object::Break => Err(error::EvaluationError::control_flow(
+            "`break` outside of a loop".to_string(),
+        )),
+        object::Object::Continue => Err(error::EvaluationError::control_flow(
+            "`continue` outside of a loop".to_string(),
+        )),
+        _ => Ok(object),
     }
 }
 
+/// Returns whether the object signals that the evaluation of the enclosing
+/// blocks should stop, i.e., a `return`, `break`, or `continue`.
+fn is_signal(object: &object::Object) -> bool {
+    matches!(
+        object,
+        object::Object::ReturnValue(_) | object::Object::Break | object::Object::Continue
+    )
+}
+
+/// The values of the keyword arguments of a call, paired with the names of
+/// the parameters they are passed to.
+type KeywordArguments = Vec<(Symbol, Rc<object::Object>)>;
+
+/// Evaluate the arguments of a call expression from left-to-right, returning
+/// the values of the positional arguments in order and the values of the
+/// keyword arguments by name.
+fn eval_arguments(
+    arguments: &[ast::Argument],
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<(Vec<Rc<object::Object>>, KeywordArguments), error::EvaluationError> {
+    let mut args = Vec::new();
+    let mut kwargs = Vec::new();
+
+    for arg in arguments {
+        match arg {
+            ast::Argument::Positional(expr) => args.push(eval_expression(expr, env, ctx)?),
+            ast::Argument::Keyword(name, expr) => {
+                kwargs.push((*name, eval_expression(expr, env, ctx)?))
+            }
+        }
+    }
+
+    Ok((args, kwargs))
+}
+
 /// Evaluate a series of expressions, returning the results of the expressions
 /// by index in an array. Expressions are evaluated from left-to-right.
 fn eval_expressions(
     expressions: &[ast::Expression],
     env: &environment::Env,
+    ctx: &mut context::Context,
 ) -> Result<Vec<Rc<object::Object>>, error::EvaluationError> {
     let mut result = Vec::new();
 
     for expr in expressions {
-        let val = eval_expression(expr, env)?;
+        let val = eval_expression(expr, env, ctx)?;
         result.push(val);
     }
 
@@ -256,14 +843,14 @@ fn eval_expressions(
 
 /// Evaluate identifier expression.
 fn eval_identifier(
-    ident: &str,
+    ident: Symbol,
     env: &environment::Env,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
     match env.borrow().get(ident) {
         Some(obj) => Ok(obj.clone()),
-        None => match Builtin::lookup(ident) {
+        None => match Builtin::lookup_symbol(ident) {
             Some(obj) => Ok(Rc::new(obj)),
-            None => Err(error::EvaluationError::new(format!(
+            None => Err(error::EvaluationError::identifier_not_found(format!(
                 "identifier not found: {}",
                 ident
             ))),
@@ -275,21 +862,209 @@ fn eval_identifier(
 fn eval_block_statement(
     statements: &[ast::Statement],
     env: &environment::Env,
+    ctx: &mut context::Context,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
     let mut result = Rc::new(object::Object::Null);
 
     for stmt in statements {
-        result = eval_statement(stmt, env)?;
+        result = eval_statement(stmt, env, ctx)?;
 
-        match *result {
-            object::Object::ReturnValue(_) => return Ok(result),
-            _ => continue,
+        if is_signal(&result) {
+            return Ok(result);
         }
     }
 
     Ok(result)
 }
 
+/// The result of evaluating a statement or expression in tail position of a
+/// function body.
+enum Tail {
+    /// The value of the statement or expression.
+    Value(Rc<object::Object>),
+    /// A call in tail position, left for the caller to apply. It is boxed to
+    /// keep results small, as they take up space in every frame that
+    /// evaluates in tail position.
+    Call(Box<TailCall>),
+}
+
+/// A call in tail position of a function body, with its function and
+/// arguments evaluated.
+struct TailCall {
+    /// The function called.
+    func: Rc<object::Object>,
+    /// The values of the positional arguments.
+    args: Vec<Rc<object::Object>>,
+    /// The values of the keyword arguments.
+    kwargs: KeywordArguments,
+    /// The environment the call was made in.
+    env: environment::Env,
+    /// The position of the call.
+    position: Position,
+}
+
+/// Evaluate the statements of a function body, or of a block in tail
+/// position of one, as [`eval_block_statement`] would, except that a call in
+/// tail position, i.e., the last expression of the block or a returned
+/// expression, is left unapplied.
+fn eval_tail_block(
+    statements: &[ast::Statement],
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Tail, error::EvaluationError> {
+    let Some((last, rest)) = statements.split_last() else {
+        return Ok(Tail::Value(Rc::new(object::Object::Null)));
+    };
+
+    for stmt in rest {
+        if let ast::Statement::Return(expr) = stmt {
+            ctx.step()?;
+            return eval_tail_expression(expr, env, ctx);
+        }
+        let result = eval_statement(stmt, env, ctx)?;
+        if is_signal(&result) {
+            return Ok(Tail::Value(result));
+        }
+    }
+
+    match last {
+        ast::Statement::Expr(expr) | ast::Statement::Return(expr) => {
+            ctx.step()?;
+            eval_tail_expression(expr, env, ctx)
+        }
+        _ => eval_statement(last, env, ctx).map(Tail::Value),
+    }
+}
+
+/// Evaluate an expression in tail position of a function body. A call is
+/// left unapplied, and the branches of conditional expressions are
+/// themselves in tail position.
+fn eval_tail_expression(
+    expression: &ast::Expression,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Tail, error::EvaluationError> {
+    if !matches!(
+        expression,
+        ast::Expression::If(..)
+            | ast::Expression::Do(..)
+            | ast::Expression::Switch(..)
+            | ast::Expression::Call(..)
+    ) {
+        return eval_expression(expression, env, ctx).map(Tail::Value);
+    }
+
+    ctx.step()?;
+    match expression {
+        ast::Expression::If(condition, consequence, alternative) => {
+            eval_tail_if(condition, consequence, alternative.as_deref(), env, ctx)
+        }
+        ast::Expression::Do(body) => eval_tail_scoped_block(body, env, ctx),
+        ast::Expression::Switch(subject, arms, position) => {
+            eval_tail_switch(subject, arms, *position, env, ctx)
+        }
+        ast::Expression::Call(func, args, position) => {
+            eval_tail_call(func, args, *position, env, ctx)
+        }
+        _ => eval_expression(expression, env, ctx).map(Tail::Value),
+    }
+}
+
+/// Evaluate an if-expression in tail position of a function body, as
+/// [`eval_if`] would, with the selected branch in tail position.
+fn eval_tail_if(
+    condition: &ast::Expression,
+    consequence: &[ast::Statement],
+    alternative: Option<&[ast::Statement]>,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Tail, error::EvaluationError> {
+    let condition = eval_expression(condition, env, ctx)?;
+    let block = if eval_condition(&condition, ctx)? {
+        consequence
+    } else {
+        match alternative {
+            Some(alt) => alt,
+            None => return Ok(Tail::Value(Rc::new(object::Object::Null))),
+        }
+    };
+    eval_tail_scoped_block(block, env, ctx)
+}
+
+/// Evaluate a switch expression in tail position of a function body, with
+/// the value of the matching arm in tail position.
+fn eval_tail_switch(
+    subject: &ast::Expression,
+    arms: &[(ast::Pattern, ast::Expression)],
+    position: token::Position,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Tail, error::EvaluationError> {
+    let subject = eval_expression(subject, env, ctx).map_err(|e| e.at(position))?;
+    match match_switch_arm(&subject, arms, env, ctx).map_err(|e| e.at(position))? {
+        Some(value) => eval_tail_expression(value, env, ctx).map_err(|e| e.at(position)),
+        None => Ok(Tail::Value(Rc::new(object::Object::Null))),
+    }
+}
+
+/// Evaluate the function and arguments of a call in tail position of a
+/// function body, leaving the call for the caller to apply.
+fn eval_tail_call(
+    func: &ast::Expression,
+    args: &[ast::Argument],
+    position: Position,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Tail, error::EvaluationError> {
+    let func = eval_expression(func, env, ctx)?;
+    let (args, kwargs) = eval_arguments(args, env, ctx)?;
+    Ok(Tail::Call(Box::new(TailCall {
+        func,
+        args,
+        kwargs,
+        env: Rc::clone(env),
+        position,
+    })))
+}
+
+/// Evaluate the statements of a block in tail position of a function body in
+/// a new environment enclosed by the given one, as [`eval_scoped_block`]
+/// would.
+fn eval_tail_scoped_block(
+    statements: &[ast::Statement],
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Tail, error::EvaluationError> {
+    let env = enclose(env, 0, ctx)?;
+    eval_tail_block(statements, &env, ctx)
+}
+
+/// Evaluate the statements of a block, e.g., the body of an `if`, in a new
+/// environment enclosed by the given one, so that bindings made with `let`
+/// are local to the block. Assignments to bindings of enclosing scopes still
+/// update them.
+fn eval_scoped_block(
+    statements: &[ast::Statement],
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let env = enclose(env, 0, ctx)?;
+    eval_block_statement(statements, &env, ctx)
+}
+
+/// Construct a new environment enclosed by the given one, with room for the
+/// given number of bindings, failing if it would exceed the context's scope
+/// depth limit.
+fn enclose(
+    outer: &environment::Env,
+    capacity: usize,
+    ctx: &context::Context,
+) -> Result<environment::Env, error::EvaluationError> {
+    let env = environment::Environment::with_capacity(outer, capacity);
+    ctx.check_depth(env.depth())?;
+    Ok(Rc::new(RefCell::new(env)))
+}
+
 /// Evaluates the given infix expression from its operator, and left and right
 /// expressions.
 fn eval_infix_expression(
@@ -307,13 +1082,73 @@ fn eval_infix_expression(
         (object::Object::String(left_str), object::Object::String(right_str)) => {
             eval_string_infix_expression(operator, left_str, right_str)
         }
-        _ => Err(error::EvaluationError::new(format!(
+        (object::Object::String(str), object::Object::Integer(count))
+            if *operator == token::Token::Asterisk =>
+        {
+            let count = repeat_count(str.len(), *count, || {
+                format!("string repetition too large: {} * {}", left, count)
+            })?;
+            Ok(Rc::new(object::Object::String(str.repeat(count))))
+        }
+        // The repeated elements share their allocations, as elements cannot
+        // be mutated in place
+        (object::Object::Array(arr), object::Object::Integer(count))
+            if *operator == token::Token::Asterisk =>
+        {
+            let count = repeat_count(arr.len(), *count, || {
+                format!("array repetition too large: {} * {}", left, count)
+            })?;
+            Ok(Rc::new(object::Object::Array(
+                arr.iter()
+                    .cycle()
+                    .take(arr.len() * count)
+                    .cloned()
+                    .collect(),
+            )))
+        }
+        // `null` is only equal to itself
+        (object::Object::Null, _) | (_, object::Object::Null)
+            if matches!(operator, token::Token::Eq | token::Token::NotEq) =>
+        {
+            let equal = matches!(
+                (&**left, &**right),
+                (object::Object::Null, object::Object::Null)
+            );
+            Ok(Rc::new(object::Object::Boolean(
+                equal == (*operator == token::Token::Eq),
+            )))
+        }
+        // Functions close over environments, so there is no meaningful sense
+        // in which two of them are equal
+        (object::Object::Function(..), _) | (_, object::Object::Function(..))
+            if matches!(operator, token::Token::Eq | token::Token::NotEq) =>
+        {
+            Err(error::EvaluationError::type_mismatch(
+                "cannot compare functions".to_string(),
+            ))
+        }
+        _ => Err(error::EvaluationError::unknown_operator(format!(
             "unknown operator: {} {} {}",
             left, operator, right
         ))),
     }
 }
 
+/// Returns the number of times to repeat a value of the given length, where a
+/// count below one repeats it zero times, failing if the repeated length would
+/// exceed [`MAX_REPEAT_LEN`].
+fn repeat_count(
+    len: usize,
+    count: i64,
+    too_large: impl FnOnce() -> String,
+) -> Result<usize, error::EvaluationError> {
+    let count = usize::try_from(count).unwrap_or(0);
+    match len.checked_mul(count) {
+        Some(total) if total <= MAX_REPEAT_LEN => Ok(count),
+        _ => Err(error::EvaluationError::invalid_argument(too_large())),
+    }
+}
+
 /// Evaluates the given string infix expression from the left and right
 /// expressions and the infix operator. Supported string operations are
 /// comparison and concatenation.
@@ -328,7 +1163,7 @@ fn eval_string_infix_expression(
         ))),
         token::Token::Eq => Ok(Rc::new(object::Object::Boolean(left_str == right_str))),
         token::Token::NotEq => Ok(Rc::new(object::Object::Boolean(left_str != right_str))),
-        _ => Err(error::EvaluationError::new(format!(
+        _ => Err(error::EvaluationError::unknown_operator(format!(
             "unknown operator: {} {} {}",
             left_str, operator, right_str
         ))),
@@ -345,7 +1180,7 @@ fn eval_boolean_infix_expression(
     match operator {
         token::Token::Eq => Ok(Rc::new(object::Object::Boolean(left_b == right_b))),
         token::Token::NotEq => Ok(Rc::new(object::Object::Boolean(left_b != right_b))),
-        _ => Err(error::EvaluationError::new(format!(
+        _ => Err(error::EvaluationError::unknown_operator(format!(
             "unknown operator: {} {} {}",
             left_b, operator, right_b
         ))),
@@ -353,27 +1188,54 @@ fn eval_boolean_infix_expression(
 }
 
 /// Evaluates the given integer infix expression from the left and right
-/// expressions and the infix arithmetic or logical operator.
+/// expressions and the infix arithmetic or logical operator. Arithmetic that
+/// overflows a 64-bit signed integer is an error.
 fn eval_integer_infix_expression(
     operator: &token::Token,
     left_int: i64,
     right_int: i64,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
-    match operator {
+    let overflow = || {
+        error::EvaluationError::overflow(format!(
+            "integer overflow: {} {} {}",
+            left_int, operator, right_int
+        ))
+    };
+    let checked = |result: Option<i64>| match result {
+        Some(int) => Ok(Rc::new(object::Object::Integer(int))),
+        None => Err(overflow()),
+    };
+
+    match operator {
         /* Arithmetic operators */
-        token::Token::Plus => Ok(Rc::new(object::Object::Integer(left_int + right_int))),
-        token::Token::Minus => Ok(Rc::new(object::Object::Integer(left_int - right_int))),
-        token::Token::Asterisk => Ok(Rc::new(object::Object::Integer(left_int * right_int))),
+        token::Token::Plus => checked(left_int.checked_add(right_int)),
+        token::Token::Minus => checked(left_int.checked_sub(right_int)),
+        token::Token::Asterisk => checked(left_int.checked_mul(right_int)),
         token::Token::Slash => match right_int {
-            0 => Err(error::EvaluationError::new("division by zero".to_string())),
-            _ => Ok(Rc::new(object::Object::Integer(left_int / right_int))),
+            0 => Err(error::EvaluationError::division_by_zero(
+                "division by zero".to_string(),
+            )),
+            _ => checked(left_int.checked_div(right_int)),
         },
+        token::Token::Power => {
+            if right_int < 0 {
+                return Err(error::EvaluationError::negative_exponent(format!(
+                    "negative exponent: {} ** {}",
+                    left_int, right_int
+                )));
+            }
+            checked(
+                u32::try_from(right_int)
+                    .ok()
+                    .and_then(|exp| left_int.checked_pow(exp)),
+            )
+        }
         /* Logical operators */
         token::Token::Gt => Ok(Rc::new(object::Object::Boolean(left_int > right_int))),
         token::Token::Lt => Ok(Rc::new(object::Object::Boolean(left_int < right_int))),
         token::Token::Eq => Ok(Rc::new(object::Object::Boolean(left_int == right_int))),
         token::Token::NotEq => Ok(Rc::new(object::Object::Boolean(left_int != right_int))),
-        _ => Err(error::EvaluationError::new(format!(
+        _ => Err(error::EvaluationError::unknown_operator(format!(
             "unknown operator: {} {} {}",
             left_int, operator, right_int
         ))),
@@ -389,7 +1251,7 @@ fn eval_prefix_expression(
     match operator {
         token::Token::Bang => eval_bang_operator_expression(right),
         token::Token::Minus => eval_minus_operator_expression(right),
-        _ => Err(error::EvaluationError::new(format!(
+        _ => Err(error::EvaluationError::unknown_operator(format!(
             "unknown operator: {}{}",
             operator, right
         ))),
@@ -402,8 +1264,14 @@ fn eval_minus_operator_expression(
     right: &Rc<object::Object>,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
     match **right {
-        object::Object::Integer(int) => Ok(Rc::new(object::Object::Integer(-int))),
-        _ => Err(error::EvaluationError::new(format!(
+        object::Object::Integer(int) => match int.checked_neg() {
+            Some(negated) => Ok(Rc::new(object::Object::Integer(negated))),
+            None => Err(error::EvaluationError::overflow(format!(
+                "integer overflow: -({})",
+                int
+            ))),
+        },
+        _ => Err(error::EvaluationError::unknown_operator(format!(
             "unknown operator: -{}",
             right
         ))),
@@ -427,22 +1295,74 @@ fn eval_bang_operator_expression(
 fn eval_statement(
     statement: &ast::Statement,
     env: &environment::Env,
+    ctx: &mut context::Context,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
+    ctx.step()?;
     match statement {
-        ast::Statement::Expr(expr) => eval_expression(expr, &Rc::clone(env)),
+        ast::Statement::Expr(expr) => eval_expression(expr, &Rc::clone(env), ctx),
         ast::Statement::Let(ident, expr) => {
-            let val = eval_expression(expr, &Rc::clone(env))?;
+            let val = eval_expression(expr, &Rc::clone(env), ctx)?;
             let obj = Rc::clone(&val);
 
             // Store value in environment
-            env.borrow_mut().set(ident, obj);
+            env.borrow_mut().set(*ident, obj);
+
+            Ok(val)
+        }
+        ast::Statement::LetHash(bindings, expr, position) => {
+            let val = eval_expression(expr, &Rc::clone(env), ctx)?;
+            let object::Object::Hash(hash) = &*val else {
+                return Err(error::EvaluationError::type_mismatch(format!(
+                    "cannot destructure {} as a hash",
+                    val.type_name()
+                ))
+                .at(*position));
+            };
+
+            // Look up every key before binding any of them
+            let values = bindings
+                .iter()
+                .map(|(key, _)| {
+                    let name = key.as_str();
+                    hash.get(&object::HashableObject::String(name.to_string()))
+                        .cloned()
+                        .ok_or_else(|| {
+                            error::EvaluationError::index_error(format!(
+                                "key not found: {:?}",
+                                name
+                            ))
+                            .at(*position)
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut env = env.borrow_mut();
+            for ((_, ident), value) in bindings.iter().zip(values) {
+                env.set(*ident, value);
+            }
+
+            Ok(val)
+        }
+        ast::Statement::Assign(ident, expr, position) => {
+            let val = eval_expression(expr, &Rc::clone(env), ctx)?;
+
+            // Update the value in the scope it is bound in
+            if !env.borrow_mut().assign(*ident, Rc::clone(&val)) {
+                return Err(error::EvaluationError::identifier_not_found(format!(
+                    "identifier not found: {}",
+                    ident
+                ))
+                .at(*position));
+            }
 
             Ok(val)
         }
         ast::Statement::Return(expr) => {
-            let val = eval_expression(expr, env)?;
+            let val = eval_expression(expr, env, ctx)?;
             Ok(Rc::new(object::Object::ReturnValue(val)))
         }
+        ast::Statement::Break => Ok(Rc::new(object::Object::Break)),
+        ast::Statement::Continue => Ok(Rc::new(object::Object::Continue)),
     }
 }
 
@@ -451,16 +1371,16 @@ fn eval_statement(
 fn eval_program(
     program: &[ast::Statement],
     env: &environment::Env,
+    ctx: &mut context::Context,
 ) -> Result<Rc<object::Object>, error::EvaluationError> {
     let mut result = Rc::new(object::Object::Null);
 
     for stmt in program {
-        result = eval_statement(stmt, &Rc::clone(env))?;
+        result = eval_statement(stmt, &Rc::clone(env), ctx)?;
 
-        // Return early if encounter a return statement
-        match *result {
-            object::Object::ReturnValue(_) => return Ok(result),
-            _ => continue,
+        // A top-level return ends the program with the returned value
+        if is_signal(&result) {
+            return unwrap_return_value(result);
         }
     }
 
@@ -490,6 +1410,182 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_eval_all() {
+        let buffer = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let mut ctx = context::Context::new(buffer.clone());
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let program = parse("let x = 5; x * 2; puts(x);").unwrap();
+        let results = eval_all_with_context(program, &env, &mut ctx)
+            .unwrap()
+            .iter()
+            .map(|obj| obj.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(results, ["5", "10", "null"]);
+        assert_eq!(buffer.borrow().as_slice(), b"5\n");
+
+        let program = parse("1; y; 2;").unwrap();
+        let err = eval_all(program, &env).unwrap_err();
+        assert_eq!(err.to_string(), "identifier not found: y");
+
+        let program = parse("1; return 2; 3;").unwrap();
+        let results = eval_all(program, &env).unwrap();
+        assert_eq!(
+            results.iter().map(|obj| &**obj).collect::<Vec<_>>(),
+            [&object::Object::Integer(1), &object::Object::Integer(2)]
+        );
+    }
+
+    #[test]
+    fn test_top_level_return() {
+        let cases = [
+            ("return 5; 6", "5"),
+            ("if (true) { return 1; } 2", "1"),
+            ("do { return 3; }; 4", "3"),
+            ("let x = 1; return x; x = 2", "1"),
+            ("let f = fn() { return 1; }; return f() + 1; 0", "2"),
+        ];
+        check_eval_case(&cases);
+
+        // The program evaluates to the returned value itself
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let result = eval(parse("if (true) { return 5; }").unwrap(), &env).unwrap();
+        assert_eq!(*result, object::Object::Integer(5));
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let cases = [
+            ("foobar", Some("1:1")),
+            ("let a = 1;\n  a + true", Some("2:5")),
+            ("-true", Some("1:1")),
+            ("5(1)", Some("1:2")),
+            ("let a = [1, 2];\na[true]", Some("2:2")),
+            (r#""abc"[true:]"#, Some("1:6")),
+            ("len(1)", Some("1:4")),
+            // The innermost expression that raised the error is reported
+            ("let f = fn() { 1 / 0 }; f()", Some("1:18")),
+            ("[1 + (2 * missing)]", Some("1:11")),
+            // Including within a self-call in tail position
+            ("let f = fn(n) { f() }; f(1)", Some("1:18")),
+            // Assignments are reported at the assigned identifier
+            ("let x = 1;\n  y = 2", Some("2:3")),
+            // Switches are reported at their subject, in or out of tail
+            // position
+            ("switch (fn() {}) { 1 => 1 }", Some("1:9")),
+            (
+                "let f = fn() { switch ([fn() {}]) { 1 => 1 } }; f()",
+                Some("1:24"),
+            ),
+            ("switch (1) { 1 => {1: 1, 1: 2} }", Some("1:9")),
+            // An error outside of any positioned expression has no position
+            ("{1: 1, 1: 2}", None),
+        ];
+
+        for (input, expected) in cases {
+            let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+            let node = parse(input).expect("failed to parse input");
+            let err = eval(node, &env).expect_err(input);
+            assert_eq!(
+                err.position()
+                    .map(|position| position.to_string())
+                    .as_deref(),
+                expected,
+                "wrong position for {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_eval_with_limit() {
+        use crate::eval::error::ErrorKind;
+
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let program = parse("let x = 1 + 2; x").unwrap();
+        let result = eval_with_limit(program.clone(), &env, 100).unwrap();
+        assert_eq!(result.to_string(), "3");
+
+        // `let`, `1 + 2`, `1`, `2`, then `x` exceeds the limit
+        let err = eval_with_limit(program, &env, 4).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::StepLimit);
+        assert_eq!(err.to_string(), "evaluation step limit exceeded: limit=4");
+
+        // A program that never terminates is stopped at the limit
+        let program = parse("let forever = fn(n) { forever(n + 1) }; forever(0)").unwrap();
+        let err = eval_with_limit(program, &env, 1000).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::StepLimit);
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        use crate::eval::error::ErrorKind;
+
+        let mut ctx = context::Context::default().with_depth_limit(50);
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+
+        // Programs nested within the limit are unaffected
+        let program = parse("let f = fn(n) { if (n > 0) { do { f(n - 1) } } else { 0 } }; f(100)");
+        let result = eval_with_context(program.unwrap(), &env, &mut ctx).unwrap();
+        assert_eq!(*result, object::Object::Integer(0));
+
+        // Deeply nested blocks fail cleanly
+        let source = format!("{}1{}", "do { ".repeat(60), " }".repeat(60));
+        let err = eval_with_context(parse(&source).unwrap(), &env, &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DepthLimit);
+        assert_eq!(err.to_string(), "scope depth limit exceeded: limit=50");
+
+        // As do scopes made within a deep chain of environments passed in
+        let mut deep = Rc::clone(&env);
+        for _ in 0..1000 {
+            deep = Rc::new(RefCell::new(
+                environment::Environment::new_enclosed_environment(&deep),
+            ));
+        }
+        let result = eval_with_context(parse("1").unwrap(), &deep, &mut ctx).unwrap();
+        assert_eq!(*result, object::Object::Integer(1));
+        let err = eval_with_context(parse("fn() { 1 }()").unwrap(), &deep, &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DepthLimit);
+    }
+
+    #[test]
+    fn test_strict_booleans() {
+        use crate::eval::error::ErrorKind;
+
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let program = parse("if (5) { 1 }").unwrap();
+
+        // Lenient by default, where any value but `false` and `null` holds
+        let result = eval(program.clone(), &env).unwrap();
+        assert_eq!(*result, object::Object::Integer(1));
+
+        let mut ctx = context::Context::default().with_strict_booleans();
+        let err = eval_with_context(program, &env, &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::TypeMismatch);
+        assert_eq!(err.to_string(), "condition must be a boolean, got INTEGER");
+
+        let cases = [
+            ("if (1 < 2) { 1 } else { 2 }", "1"),
+            ("if (false) { 1 } else { 2 }", "2"),
+            (
+                "if (null) { 1 } else { 2 }",
+                "condition must be a boolean, got NULL",
+            ),
+            // Including conditions in tail position of a function
+            (
+                "let f = fn(x) { if (x) { 1 } }; f([])",
+                "condition must be a boolean, got ARRAY",
+            ),
+        ];
+        for (input, expected) in cases {
+            let result = eval_with_context(parse(input).unwrap(), &env, &mut ctx);
+            match result {
+                Ok(value) => assert_eq!(value.to_string(), expected, "{}", input),
+                Err(e) => assert_eq!(e.to_string(), expected, "{}", input),
+            }
+        }
+    }
+
     #[test]
     fn test_eval_integer_expression() {
         let int_cases = [
@@ -508,6 +1604,40 @@ mod tests {
             ("3 * 3 * 3 + 10", "37"),
             ("3 * (3 * 3) + 10", "37"),
             ("(5 + 10 * 2 + 15 / 3) * 2 + -10", "50"),
+            ("2 ** 10", "1024"),
+            ("2 ** 2 ** 3", "256"),
+            ("(2 ** 2) ** 3", "64"),
+            ("3 * 2 ** 2", "12"),
+            ("5 ** 0", "1"),
+            ("2 ** 63", "integer overflow: 2 ** 63"),
+            ("3000000000 * 2", "6000000000"),
+            (
+                "9223372036854775807 + 1",
+                "integer overflow: 9223372036854775807 + 1",
+            ),
+            (
+                "-9223372036854775807 - 2",
+                "integer overflow: -9223372036854775807 - 2",
+            ),
+            (
+                "4611686018427387904 * 2",
+                "integer overflow: 4611686018427387904 * 2",
+            ),
+            (
+                "(-9223372036854775807 - 1) / -1",
+                "integer overflow: -9223372036854775808 / -1",
+            ),
+            (
+                "-(-9223372036854775807 - 1)",
+                "integer overflow: -(-9223372036854775808)",
+            ),
+            ("-9223372036854775808", "-9223372036854775808"),
+            ("-9223372036854775808 + 1", "-9223372036854775807"),
+            (
+                "--9223372036854775808",
+                "integer overflow: -(-9223372036854775808)",
+            ),
+            ("2 ** -1", "negative exponent: 2 ** -1"),
         ];
         check_eval_case(&int_cases);
     }
@@ -538,6 +1668,41 @@ mod tests {
         check_eval_case(&int_cases);
     }
 
+    #[test]
+    fn test_null_literal() {
+        let cases = [
+            ("null", "null"),
+            ("let x = null; x == null", "true"),
+            ("1 == null", "false"),
+            ("null != 1", "true"),
+            ("null != null", "false"),
+            ("fn() { return null; }() == null", "true"),
+            ("if (null) { 1 } else { 2 }", "2"),
+            ("!null", "true"),
+            ("null + 1", "unknown operator: null + 1"),
+            ("null < null", "unknown operator: null < null"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_null_equality() {
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let check = |input: &str, expected: bool| {
+            let result = eval(parse(input).unwrap(), &env).expect(input);
+            assert_eq!(*result, object::Object::Boolean(expected), "{}", input);
+        };
+
+        check("null == null", true);
+        check("null != null", false);
+        for other in ["0", "false", r#""""#, "[]", "{}", "fn() {}", "len"] {
+            check(&format!("null == {}", other), false);
+            check(&format!("{} == null", other), false);
+            check(&format!("null != {}", other), true);
+            check(&format!("{} != null", other), true);
+        }
+    }
+
     #[test]
     fn test_bang_operator() {
         let bang_cases = [
@@ -565,6 +1730,26 @@ mod tests {
         check_eval_case(&if_else_cases);
     }
 
+    #[test]
+    fn test_else_if_expressions() {
+        let cases = [
+            (
+                "let x = 1; if (x == 1) { 10 } else if (x == 2) { 20 } else { 30 }",
+                "10",
+            ),
+            (
+                "let x = 2; if (x == 1) { 10 } else if (x == 2) { 20 } else { 30 }",
+                "20",
+            ),
+            (
+                "let x = 3; if (x == 1) { 10 } else if (x == 2) { 20 } else { 30 }",
+                "30",
+            ),
+            ("if (false) { 10 } else if (false) { 20 }", "null"),
+        ];
+        check_eval_case(&cases);
+    }
+
     #[test]
     fn test_return_statements() {
         let return_cases = [
@@ -595,7 +1780,7 @@ mod tests {
             ("true + false;", "unknown operator: true + false"),
             ("5; true + false; 5", "unknown operator: true + false"),
             (
-                "if (10 > 1) { true + false; )",
+                "if (10 > 1) { true + false; }",
                 "unknown operator: true + false",
             ),
             ("foobar", "identifier not found: foobar"),
@@ -604,6 +1789,38 @@ mod tests {
         check_eval_case(&error_cases);
     }
 
+    #[test]
+    fn test_error_kinds() {
+        use crate::eval::error::ErrorKind;
+
+        let cases = [
+            ("5 + true;", ErrorKind::UnknownOperator),
+            ("foobar", ErrorKind::IdentifierNotFound),
+            ("5(1)", ErrorKind::NotAFunction),
+            ("fn(x) { x }(1, 2)", ErrorKind::WrongArgCount),
+            ("len(1, 2)", ErrorKind::WrongArgCount),
+            ("fn(x) { x }(y: 1)", ErrorKind::InvalidKeyword),
+            ("len(1)", ErrorKind::TypeMismatch),
+            ("char(-1)", ErrorKind::InvalidArgument),
+            ("1 / 0", ErrorKind::DivisionByZero),
+            ("2 ** 64", ErrorKind::Overflow),
+            ("2 ** -1", ErrorKind::NegativeExponent),
+            ("1[0]", ErrorKind::IndexError),
+            ("{1: 1, 1: 2}", ErrorKind::DuplicateKey),
+            ("fn() {} == fn() {}", ErrorKind::TypeMismatch),
+            (r#"import("does-not-exist.monkey")"#, ErrorKind::Io),
+        ];
+
+        for (input, expected) in cases {
+            let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+            let node = parse(input).expect("failed to parse input");
+            match eval(node, &env) {
+                Ok(obj) => panic!("expected error for {}, got {}", input, obj),
+                Err(e) => assert_eq!(e.kind(), expected, "wrong kind for {}", input),
+            }
+        }
+    }
+
     #[test]
     fn test_let_statement() {
         let let_stmts = [
@@ -611,10 +1828,28 @@ mod tests {
             ("let a = 5 * 5; a;", "25"),
             ("let a = 5; let b = a; b;", "5"),
             ("let a = 5; let b = a; let c = a + b + 5; c;", "15"),
+            ("let café = 5; let _π = café * 2; _π", "10"),
         ];
         check_eval_case(&let_stmts);
     }
 
+    #[test]
+    fn test_let_hash_statement() {
+        let cases = [
+            (
+                r#"let person = {"name": "Ann", "age": 30}; let {name, age} = person; [name, age]"#,
+                "[Ann, 30]",
+            ),
+            (r#"let {name: n} = {"name": "Bob"}; n"#, "Bob"),
+            (
+                r#"let {name, missing} = {"name": "Cy"}"#,
+                r#"key not found: "missing""#,
+            ),
+            ("let {a} = [1]", "cannot destructure ARRAY as a hash"),
+        ];
+        check_eval_case(&cases);
+    }
+
     #[test]
     fn test_function_object() {
         let func_objs = [("fn(x) { x + 2; }", "fn(x) {\n (x + 2) \n}")];
@@ -633,10 +1868,131 @@ mod tests {
                 "20",
             ),
             ("fn(x) { x; }(5)", "5"),
+            ("fn add(a, b) { a + b } add(2, 3)", "5"),
+            (
+                "fn fact(n) { if (n < 2) { 1 } else { n * fact(n - 1) } } fact(5)",
+                "120",
+            ),
+            // A line starting with an operator continues the expression
+            ("fn(a, b) { a\n - b }(5, 2)", "3"),
         ];
         check_eval_case(&func_apps);
     }
 
+    #[test]
+    fn test_tail_calls() {
+        let cases = [
+            // Deep enough to overflow the stack without tail calls
+            (
+                "let count = fn(n) { if (n == 0) { 0 } else { count(n - 1) } }; count(1000000)",
+                "0",
+            ),
+            (
+                "let sum = fn(n, acc) { if (n == 0) { return acc; } return sum(n - 1, acc + n); };\
+                 sum(1000000, 0)",
+                "500000500000",
+            ),
+            (
+                "let f = fn(n) { switch (n) { 0 => \"done\", _ => do { let m = n - 1; f(m) } } };\
+                 f(100000)",
+                "done",
+            ),
+            // Arguments are rebound on each iteration
+            ("let f = fn(n, acc = []) { if (n == 0) { acc } else { f(n - 1, push(acc, n)) } }; f(3)", "[3, 2, 1]"),
+            // Calls to other functions and builtins in tail position
+            ("let g = fn(x) { x * 2 }; let f = fn(x) { g(x + 1) }; f(1)", "4"),
+            ("let f = fn() { let x = 1; if (true) { let x = 2; eval(\"x\") } }; f()", "2"),
+            // Calls outside of tail position are applied as before
+            ("let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(100)", "100"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_default_parameters() {
+        let cases = [
+            ("let f = fn(x, y = 10) { x + y }; f(5)", "15"),
+            ("let f = fn(x, y = 10) { x + y }; f(5, 1)", "6"),
+            ("let f = fn(x, y = x * 2) { x + y }; f(5)", "15"),
+            ("let z = 3; let f = fn(x = z) { x }; f()", "3"),
+            (
+                "let f = fn(x, y = 10) { x + y }; f()",
+                "invalid number of arguments: expected=1..2, got=0",
+            ),
+            (
+                "let f = fn(x, y = 10) { x + y }; f(1, 2, 3)",
+                "invalid number of arguments: expected=1..2, got=3",
+            ),
+            (
+                "let f = fn(x) { x }; f()",
+                "invalid number of arguments: expected=1, got=0",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_rest_parameters() {
+        let cases = [
+            ("let f = fn(a, ...b) { b }; f(1, 2, 3)", "[2, 3]"),
+            ("let f = fn(a, ...b) { b }; f(1)", "[]"),
+            ("let f = fn(...args) { len(args) }; f()", "0"),
+            (
+                "let f = fn(a, b = 2, ...c) { [a, b, c] }; f(1)",
+                "[1, 2, []]",
+            ),
+            (
+                "let f = fn(a, b = 2, ...c) { [a, b, c] }; f(1, 3, 5, 7)",
+                "[1, 3, [5, 7]]",
+            ),
+            (
+                "let f = fn(a, ...b) { b }; f()",
+                "invalid number of arguments: expected=1.., got=0",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_keyword_arguments() {
+        let cases = [
+            ("let f = fn(x, y) { x - y }; f(y: 1, x: 3)", "2"),
+            (
+                "let f = fn(x, y, z) { [x, y, z] }; f(1, z: 3, y: 2)",
+                "[1, 2, 3]",
+            ),
+            (
+                "let f = fn(x, y = 2, z = 3) { [x, y, z] }; f(1, z: 4)",
+                "[1, 2, 4]",
+            ),
+            (
+                "let f = fn(x, y) { x - y }; f(1, w: 2)",
+                "unknown keyword argument: w",
+            ),
+            (
+                "let f = fn(x, y) { x - y }; f(1, x: 2)",
+                "multiple values for argument: x",
+            ),
+            (
+                "let f = fn(x, y) { x - y }; f(x: 1, x: 2)",
+                "multiple values for argument: x",
+            ),
+            (
+                "let f = fn(x, y) { x - y }; f(x: 1)",
+                "invalid number of arguments: expected=2, got=1",
+            ),
+            (
+                "let f = fn(x, ...rest) { rest }; f(1, rest: 2)",
+                "unknown keyword argument: rest",
+            ),
+            (
+                "len(x: [1])",
+                "keyword arguments are not supported by builtin: len",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
     #[test]
     fn test_closures() {
         let input = [(
@@ -653,74 +2009,882 @@ mod tests {
     }
 
     #[test]
-    fn test_string_literal() {
-        let input = [("\"Hello World!\"", "Hello World!")];
-        check_eval_case(&input);
-    }
-
-    #[test]
-    fn test_string_concatenation() {
-        let input = [("\"Hello\" + \" \" + \"World!\"", "Hello World!")];
-        check_eval_case(&input);
+    fn test_call_environments() {
+        let cases = [
+            // Each call gets its own environment, so recursive calls do not
+            // see each other's parameters
+            (
+                "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(15)",
+                "610",
+            ),
+            (
+                "let f = fn(n, acc) { if (n == 0) { acc } else { f(n - 1, push(acc, n)) } }; f(3, [])",
+                "[3, 2, 1]",
+            ),
+            // Mutual recursion through the enclosing environment
+            (
+                "let even = fn(n) { if (n == 0) { true } else { odd(n - 1) } };\
+                 let odd = fn(n) { if (n == 0) { false } else { even(n - 1) } };\
+                 [even(10), odd(7), even(3)]",
+                "[true, true, false]",
+            ),
+            // Closures keep the environment of the call that created them
+            (
+                "let counter = fn() { let n = 0; fn() { n = n + 1; n } };\
+                 let a = counter(); let b = counter();\
+                 a(); a(); b(); [a(), b()]",
+                "[3, 2]",
+            ),
+            (
+                "let f = fn(x) { fn(y) { fn(z) { [x, y, z] } } }; let g = f(1); [g(2)(3), g(4)(5)]",
+                "[[1, 2, 3], [1, 4, 5]]",
+            ),
+            // Parameters shadow outer bindings without changing them
+            ("let x = 1; let f = fn(x) { x = x + 10; x }; [f(5), x]", "[15, 1]"),
+            // Arities other than one argument per parameter take the
+            // general path
+            ("let f = fn(a, b) { a + b }; f(1)", "invalid number of arguments: expected=2, got=1"),
+            ("let f = fn(a) { a }; f(a: 7)", "7"),
+        ];
+        check_eval_case(&cases);
     }
 
     #[test]
-    fn test_builtin_functions() {
+    fn test_switch_expression() {
         let cases = [
-            (r#"len("")"#, "0"),
-            (r#"len("four")"#, "4"),
-            (r#"len("hello world")"#, "11"),
-            ("len(1)", "argument to `len` not supported, got 1"),
             (
-                r#"len("one", "two")"#,
-                "wrong number of arguments: expected=1, got=2",
+                r#"let name = fn(x) { switch (x) { 1 => "one", 2 => "two", _ => "other" } }; [name(1), name(2), name(3)]"#,
+                "[one, two, other]",
             ),
-            (r#"len([])"#, "0"),
+            (r#"switch ("b") { "a" => 1, "b" => 2, _ => 3 }"#, "2"),
+            ("switch (1 + 1) { 1 + 1 => true, _ => false }", "true"),
+            ("switch (true) { false => 0 }", "null"),
+            ("switch (5) {}", "null"),
+            ("switch (null) { null => 1, _ => 2 }", "1"),
+            // Only the matching arm is evaluated
+            ("switch (1) { 1 => 1, 2 => missing }", "1"),
+            ("switch (1) { _ => 0 } + 1", "1"),
+            // Patterns of a different type than the subject do not match
+            (r#"switch (1) { "1" => 1 }"#, "null"),
+            (
+                r#"switch ("a") { 1 => "one", "a" => "A", _ => "other" }"#,
+                "A",
+            ),
+            (
+                r#"let kind = fn(x) { switch (x) { 0 => "zero", "" => "empty", [] => "nil", null => "null", _ => "other" } }; [kind(0), kind(""), kind([]), kind(null), kind(false)]"#,
+                "[zero, empty, nil, null, other]",
+            ),
+            ("switch ([1, [2]]) { [1, [2]] => true, _ => false }", "true"),
+            ("switch (fn() {}) { 1 => 1 }", "cannot compare functions"),
         ];
         check_eval_case(&cases);
     }
 
     #[test]
-    fn test_array_literals() {
-        let cases = [("[1, 2 * 2, 3 + 3]", "[1, 4, 6]")];
+    fn test_do_expression() {
+        let cases = [
+            ("let y = do { let z = 5; z * 2 }; y", "10"),
+            (
+                "let y = do { let z = 5; z * 2 }; z",
+                "identifier not found: z",
+            ),
+            ("do {}", "null"),
+            ("let x = 1; do { let x = 2; x } + x", "3"),
+            ("let x = 1; do { x = 2; }; x", "2"),
+            // Returns propagate out of the block to the enclosing function
+            ("let f = fn() { do { return 1; }; 2 }; f()", "1"),
+        ];
         check_eval_case(&cases);
     }
 
     #[test]
-    fn test_array_index_expressions() {
-        let index_cases = [
-            ("[1, 2, 3][0]", "1"),
-            ("[1, 2, 3][1]", "2"),
-            ("[1, 2, 3][2]", "3"),
-            ("let i = 0; [1][i];", "1"),
-            ("[1, 2, 3][1 + 1];", "3"),
-            ("let myArray = [1, 2, 3]; myArray[2];", "3"),
+    fn test_for_expression() {
+        let cases = [
+            ("let sum = 0; for (x in [1, 2, 3]) { sum = sum + x }; sum", "6"),
+            ("for (x in []) { x }", "null"),
+            ("for (x in [1]) { x }", "null"),
             (
-                "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
-                "6",
+                r#"let keys = ""; for (entry in {"b": 2, "a": 1}) { keys = keys + entry[0] }; keys"#,
+                "ab",
             ),
+            // The element is bound in a new scope for each iteration
+            ("let x = 0; for (x in [1, 2]) { x }; x", "0"),
+            ("for (y in [1]) { let z = y }; z", "identifier not found: z"),
+            // Returns propagate out of the loop to the enclosing function
             (
-                "let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]",
-                "2",
+                "let find = fn(xs) { for (x in xs) { if (x > 1) { return x; } }; -1 }; find([1, 5, 3])",
+                "5",
             ),
-            ("[1, 2, 3][3]", "null"),
-            ("[1, 2, 3][-1]", "null"),
-            ("first([1, 2, 3])", "1"),
-            ("first([])", "null"),
-            ("first(1)", "argument to `first` must be ARRAY, got 1"),
-            ("last([1, 2, 3])", "3"),
-            ("last([])", "null"),
-            ("last(1)", "argument to `last` must be ARRAY, got 1"),
-            ("rest([1, 2, 3])", "[2, 3]"),
-            ("rest([])", "null"),
-            ("push([], 1)", "[1]"),
-            ("push(1, 1)", "argument to `push` must be ARRAY, got 1"),
+            ("for (x in 5) { x }", "cannot iterate over INTEGER"),
         ];
-        check_eval_case(&index_cases);
+        check_eval_case(&cases);
     }
 
     #[test]
-    fn test_hash_literals() {
+    fn test_break_and_continue() {
+        let cases = [
+            (
+                "let sum = 0; for (x in [1, 2, 3, 4]) { if (x == 3) { break; } sum = sum + x }; sum",
+                "3",
+            ),
+            (
+                "let sum = 0; for (x in [1, 2, 3, 4]) { if (x == 2) { continue; } sum = sum + x }; sum",
+                "8",
+            ),
+            // Only the innermost loop is affected
+            (
+                "let n = 0; for (x in [1, 2]) { for (y in [1, 2]) { break; }; n = n + 1 }; n",
+                "2",
+            ),
+            (
+                "let f = fn() { for (x in [1, 2]) { do { break; } }; \"done\" }; f()",
+                "done",
+            ),
+            ("break", "`break` outside of a loop"),
+            ("if (true) { continue; }", "`continue` outside of a loop"),
+            // A function body is not within the loop it is called from
+            (
+                "let f = fn() { break; }; for (x in [1]) { f() }",
+                "`break` outside of a loop",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_block_scoping() {
+        let cases = [
+            ("if (true) { let x = 1; } x", "identifier not found: x"),
+            ("if (false) { 1 } else { let y = 2; } y", "identifier not found: y"),
+            ("if (true) { let x = 1; x }", "1"),
+            // Block-local bindings shadow outer ones only within the block
+            ("let x = 1; if (true) { let x = 2; x }", "2"),
+            ("let x = 1; if (true) { let x = 2; } x", "1"),
+            // Assignment updates the binding of the enclosing scope
+            ("let x = 1; if (true) { x = 2; } x", "2"),
+            ("let f = fn() { if (true) { let a = 1; } a }; f()", "identifier not found: a"),
+            // Closures over block-locals keep them alive
+            ("let f = if (true) { let n = 41; fn() { n + 1 } }; f()", "42"),
+            (
+                "let counter = if (true) { let n = 0; fn() { n = n + 1; n } }; counter(); counter()",
+                "2",
+            ),
+            // Returns still propagate out of nested blocks
+            ("let f = fn() { if (true) { let r = 3; return r; } 4 }; f()", "3"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_assignment() {
+        let cases = [
+            ("let a = 1; a = 2; a", "2"),
+            ("let a = 1; a = a + 1", "2"),
+            ("let a = 1; let f = fn() { a = 10; }; f(); a", "10"),
+            (
+                "let a = 1; let f = fn() { let a = 5; a = 10; }; f(); a",
+                "1",
+            ),
+            ("b = 1", "identifier not found: b"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_closure_sees_later_mutation() {
+        // Closures share their defining environment rather than snapshotting
+        // it, so a reassignment after the closure is created is visible.
+        let cases = [
+            ("let c = 0; let f = fn() { c }; c = 5; f()", "5"),
+            (
+                "let counter = fn() { let count = 0; fn() { count = count + 1; count } }; \
+                 let next = counter(); next(); next(); next()",
+                "3",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_string_literal() {
+        let input = [("\"Hello World!\"", "Hello World!")];
+        check_eval_case(&input);
+    }
+
+    #[test]
+    fn test_raw_string_literal() {
+        check_eval_case(&[
+            (r#"len(r"a\nb")"#, "4"),
+            (r#"len("a\nb")"#, "3"),
+            (r#"r"\d+" == "\\d+""#, "true"),
+        ]);
+    }
+
+    #[test]
+    fn test_string_concatenation() {
+        let input = [("\"Hello\" + \" \" + \"World!\"", "Hello World!")];
+        check_eval_case(&input);
+    }
+
+    #[test]
+    fn test_string_repetition() {
+        let cases = [
+            (r#""ab" * 3"#, "ababab"),
+            (r#""x" * 0"#, ""),
+            (r#""x" * -2"#, ""),
+            (r#""" * 1000000000000"#, ""),
+            (r#""-" * 2 + "|""#, "--|"),
+            (r#"len("é" * 3)"#, "3"),
+            (
+                r#""ab" * 10000000000"#,
+                "string repetition too large: ab * 10000000000",
+            ),
+            (r#"3 * "ab""#, "unknown operator: 3 * ab"),
+            (r#""ab" - 3"#, "unknown operator: ab - 3"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_array_repetition() {
+        let cases = [
+            ("[1, 2] * 2", "[1, 2, 1, 2]"),
+            ("[0] * 3", "[0, 0, 0]"),
+            ("[1, 2] * 0", "[]"),
+            ("[1] * -1", "[]"),
+            ("[] * 1000000000000", "[]"),
+            ("[[1]] * 2", "[[1], [1]]"),
+            ("len([0] * 100)", "100"),
+            (
+                "[1, 2] * 10000000000",
+                "array repetition too large: [1, 2] * 10000000000",
+            ),
+            ("2 * [1]", "unknown operator: 2 * [1]"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_builtin_functions() {
+        let cases = [
+            (r#"len("")"#, "0"),
+            (r#"len("four")"#, "4"),
+            (r#"len("hello world")"#, "11"),
+            (r#"len("héllo")"#, "5"),
+            ("len(1)", "argument to `len` not supported, got 1"),
+            (
+                r#"len("one", "two")"#,
+                "wrong number of arguments: expected=1, got=2",
+            ),
+            (r#"len([])"#, "0"),
+            (r#"len({"a": 1, "b": 2})"#, "2"),
+            ("len({})", "0"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_clone_builtin() {
+        let cases = [
+            ("clone([1, [2, 3]])", "[1, [2, 3]]"),
+            (r#"clone({"a": [1]})["a"]"#, "[1]"),
+            ("clone(5)", "5"),
+            (r#"clone("abc")"#, "abc"),
+            ("clone(fn(x) { x })(2)", "2"),
+            ("clone()", "wrong number of arguments: expected=1, got=0"),
+        ];
+        check_eval_case(&cases);
+
+        // Nested collections of the copy are fresh allocations, so mutating
+        // them cannot affect the original
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let program = parse(r#"let a = [[1], {"k": [2]}]; [a, clone(a)]"#).unwrap();
+        let result = eval(program, &env).unwrap();
+        let object::Object::Array(pair) = &*result else {
+            panic!("expected array, got {}", result);
+        };
+        let (object::Object::Array(original), object::Object::Array(copy)) = (&*pair[0], &*pair[1])
+        else {
+            panic!("expected arrays, got {}", result);
+        };
+        assert_eq!(original, copy);
+        for (original, copy) in original.iter().zip(copy) {
+            assert!(!Rc::ptr_eq(original, copy));
+        }
+        let (object::Object::Hash(original), object::Object::Hash(copy)) =
+            (&*original[1], &*copy[1])
+        else {
+            panic!("expected hashes, got {}", result);
+        };
+        let key = Rc::new(object::HashableObject::String("k".to_string()));
+        assert!(!Rc::ptr_eq(&original[&key], &copy[&key]));
+    }
+
+    #[test]
+    fn test_eval_builtin() {
+        let cases = [
+            (r#"eval("let x = 41; x + 1")"#, "42"),
+            // Bindings are made in, and read from, the caller's environment
+            (r#"eval("let y = 1;"); y + 1"#, "2"),
+            (r#"let z = 2; eval("z * 3")"#, "6"),
+            (r#"let f = fn() { eval("let w = 1;"); w }; f()"#, "1"),
+            (
+                r#"let f = fn() { eval("let v = 1;") }; f(); v"#,
+                "identifier not found: v",
+            ),
+            (r#"eval("")"#, "null"),
+            (r#"eval("return 5; 6")"#, "5"),
+            (r#"eval("1 + true")"#, "unknown operator: 1 + true"),
+            (
+                r#"eval("let = 1")"#,
+                "could not parse argument to `eval`: Encountered 1 error(s) while parsing:\n\
+                 Expected identifier after 'let'",
+            ),
+            ("eval(1)", "argument to `eval` must be STRING, got 1"),
+        ];
+        check_eval_case(&cases);
+
+        // Errors within the source are reported at the call
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let err = eval(parse(r#"1; eval("1 + true")"#).unwrap(), &env).unwrap_err();
+        assert_eq!(
+            err.position().map(|p| p.to_string()).as_deref(),
+            Some("1:8")
+        );
+    }
+
+    #[test]
+    fn test_sum_builtin() {
+        let cases = [
+            ("sum([1, 2, 3, 4])", "10"),
+            ("sum([])", "0"),
+            ("sum([-5, 5, 7])", "7"),
+            (
+                "sum([9223372036854775807, 1])",
+                "integer overflow: 9223372036854775807 + 1",
+            ),
+            (
+                r#"sum([1, "2"])"#,
+                "elements of argument to `sum` must be INTEGER, got 2",
+            ),
+            ("sum(5)", "argument to `sum` must be ARRAY, got 5"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_concat_builtin() {
+        let cases = [
+            ("concat([1, 2], [3], [4, 5])", "[1, 2, 3, 4, 5]"),
+            ("concat([1], [])", "[1]"),
+            ("concat([[1, 2], [3], [[4]]])", "[1, 2, 3, [4]]"),
+            ("concat([])", "[]"),
+            ("concat([[1]], [[2]])", "[[1], [2]]"),
+            ("concat([1], 2)", "arrays to `concat` must be ARRAY, got 2"),
+            ("concat([1, 2])", "arrays to `concat` must be ARRAY, got 1"),
+            ("concat(1)", "argument to `concat` must be ARRAY, got 1"),
+            (
+                "concat()",
+                "wrong number of arguments: expected at least 1, got=0",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_zip_builtin() {
+        let cases = [
+            (r#"zip([1, 2, 3], ["a", "b"])"#, "[[1, a], [2, b]]"),
+            (r#"zip([1, 2, 3], ["a", "b"])[1][1] == "b""#, "true"),
+            ("zip([], [1])", "[]"),
+            ("zip([[1]], [true])", "[[[1], true]]"),
+            (
+                "zip([1], 2)",
+                "arguments to `zip` must be ARRAY, got [1] and 2",
+            ),
+            ("zip([1])", "wrong number of arguments: expected=2, got=1"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_to_array_builtin() {
+        let cases = [
+            (r#"to_array("abc")"#, "[a, b, c]"),
+            (r#"to_array("abc")[2] == "c""#, "true"),
+            (r#"to_array("héllo")[1]"#, "é"),
+            (r#"to_array("")"#, "[]"),
+            ("to_array([1, [2]])", "[1, [2]]"),
+            (r#"to_array({"b": 2, "a": 1})"#, "[[a, 1], [b, 2]]"),
+            (
+                "to_array(5)",
+                "argument to `to_array` must be STRING, ARRAY, or HASH, got 5",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_hashable_round_trip() {
+        let objects = [
+            object::Object::Integer(-4),
+            object::Object::Boolean(true),
+            object::Object::String("key".to_string()),
+        ];
+        for obj in objects {
+            let key = obj.as_hashable().expect("object should be hashable");
+            assert_eq!(object::Object::from(&key), obj);
+        }
+    }
+
+    #[test]
+    fn test_object_to_source() {
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let source = "fn(x, y = 2, ...rest) { if (x > y) { return [x, \"a\\n\"]; } else { y } }";
+        let function = eval(parse(source).unwrap(), &env).unwrap();
+
+        // The rendered function parses back to the same AST, up to the
+        // positions of its nodes, which the displayed form leaves out
+        let rendered = function.to_source().expect("function should render");
+        assert_eq!(
+            parse(&rendered).unwrap().to_string(),
+            parse(source).unwrap().to_string()
+        );
+
+        let cases = [
+            ("[1, -2, true, null]", Some("[1, -2, true, null]")),
+            (r#"{"k": "v\"w"}"#, Some(r#"{"k": "v\"w"}"#)),
+            ("[fn(a) { a }]", Some("[fn(a) {\n  a;\n}]")),
+            ("len", None),
+            ("[1, len]", None),
+            // A closure over the bindings of a call cannot be rendered
+            ("fn(x) { fn(y) { x + y } }(1)", None),
+        ];
+        for (input, expected) in cases {
+            let object = eval(parse(input).unwrap(), &env).unwrap();
+            assert_eq!(object.to_source().as_deref(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_merge_builtin() {
+        let cases = [
+            (
+                r#"entries(merge({"a": 1, "b": 2}, {"b": 3, "c": 4}))"#,
+                "[[a, 1], [b, 3], [c, 4]]",
+            ),
+            (
+                r#"entries(merge({"a": 1}, {}, {"a": 2}, {"a": 3}))"#,
+                "[[a, 3]]",
+            ),
+            // The merged hashes are left unchanged
+            (r#"let h = {"a": 1}; merge(h, {"a": 2}); h["a"]"#, "1"),
+            (
+                r#"merge({"a": 1}, [1])"#,
+                "arguments to `merge` must be HASH, got [1]",
+            ),
+            (
+                "merge({})",
+                "wrong number of arguments: expected at least 2, got=1",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_get_builtin() {
+        let cases = [
+            (r#"get({"a": 1}, "a", 0)"#, "1"),
+            (r#"get({"a": 1}, "z", -1)"#, "-1"),
+            (r#"get({"a": null}, "a", 1)"#, "null"),
+            ("get({1: true}, 1, false)", "true"),
+            (r#"get({"a": 1}, [1], 0)"#, "unusable as hash key: [1]"),
+            (
+                r#"get([1], 0, 0)"#,
+                "argument to `get` must be HASH, got [1]",
+            ),
+            (
+                r#"get({"a": 1}, "a")"#,
+                "wrong number of arguments: expected=3, got=2",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_set_builtin() {
+        let cases = [
+            (r#"entries(set({"a": 1}, "b", 2))"#, "[[a, 1], [b, 2]]"),
+            (r#"set({"a": 1}, "a", [2])["a"]"#, "[2]"),
+            // The original hash is left unchanged
+            (
+                r#"let h = {"a": 1}; let g = set(h, "a", 2); [h["a"], g["a"], len(entries(h))]"#,
+                "[1, 2, 1]",
+            ),
+            (
+                r#"let h = {}; set(h, "new", 1); get(h, "new", "missing")"#,
+                "missing",
+            ),
+            (
+                "set({}, fn() {}, 1)",
+                "unusable as hash key: fn() {
+  
+}",
+            ),
+            ("set(1, 2, 3)", "argument to `set` must be HASH, got 1"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_entries_builtin() {
+        let cases = [
+            (r#"entries({"a": 1})"#, "[[a, 1]]"),
+            (
+                r#"entries({"b": 2, "a": 1, "c": 3})"#,
+                "[[a, 1], [b, 2], [c, 3]]",
+            ),
+            ("entries({true: 1, 2: [3]})", "[[2, [3]], [true, 1]]"),
+            ("entries({})", "[]"),
+            (r#"entries({"a": 1})[0][0] == "a""#, "true"),
+            (
+                "entries([1, 2])",
+                "argument to `entries` must be HASH, got [1, 2]",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_format_builtin() {
+        let cases = [
+            (r#"format("{} + {} = {}", 1, 2, 3)"#, "1 + 2 = 3"),
+            (r#"format("no placeholders")"#, "no placeholders"),
+            (r#"format("{}{}", "a", [1, "b"])"#, "a[1, b]"),
+            (r#"format("{{}} {{{}}}", 1)"#, "{} {1}"),
+            (r#"format("é{}é", true)"#, "étrueé"),
+            (
+                r#"format("{} and {}", 1)"#,
+                "wrong number of arguments to `format`: template has 2 placeholder(s), got 1",
+            ),
+            (
+                r#"format("{}", 1, 2)"#,
+                "wrong number of arguments to `format`: template has 1 placeholder(s), got 2",
+            ),
+            (
+                r#"format("{")"#,
+                "unmatched `{` in format string, use `{{` for a literal brace",
+            ),
+            (
+                r#"format("a } b")"#,
+                "unmatched `}` in format string, use `}}` for a literal brace",
+            ),
+            ("format(1)", "template to `format` must be STRING, got 1"),
+            (
+                "format()",
+                "wrong number of arguments: expected at least 1, got=0",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_builtin_lookup() {
+        for builtin in Builtin::ALL {
+            assert_eq!(
+                Builtin::lookup(&builtin.to_string()),
+                Some(object::Object::Builtin(builtin.clone()))
+            );
+        }
+        assert_eq!(Builtin::lookup("not_a_builtin"), None);
+
+        let cases = [
+            ("let len = fn(x) { 42 }; len([1, 2])", "42"),
+            ("let f = fn(push) { push }; f(1)", "1"),
+            ("push", "push"),
+            ("lenn([1])", "identifier not found: lenn"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_builtin_environment_access() {
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let ctx = &mut context::Context::default();
+        let source = |src: &str| Rc::new(object::Object::String(src.to_string()));
+
+        // Builtins bind names in, and read names from, the given environment
+        Builtin::Eval
+            .apply(&[source("let x = 41;")], &env, ctx)
+            .unwrap();
+        assert_eq!(
+            env.borrow().get(Symbol::intern("x")).as_deref(),
+            Some(&object::Object::Integer(41))
+        );
+        let result = Builtin::Eval.apply(&[source("x + 1")], &env, ctx).unwrap();
+        assert_eq!(*result, object::Object::Integer(42));
+
+        // An enclosed environment leaves the outer one untouched
+        let inner: environment::Env = Rc::new(RefCell::new(
+            environment::Environment::new_enclosed_environment(&env),
+        ));
+        Builtin::Eval
+            .apply(&[source("let y = x;")], &inner, ctx)
+            .unwrap();
+        assert!(inner.borrow().get(Symbol::intern("y")).is_some());
+        assert!(env.borrow().get(Symbol::intern("y")).is_none());
+
+        // Builtins never bind names of their own for plain calls
+        Builtin::Len.apply(&[source("abc")], &env, ctx).unwrap();
+        assert_eq!(env.borrow().bindings().len(), 1);
+    }
+
+    #[test]
+    fn test_predicate_builtins() {
+        let cases = [
+            ("is_null(null)", "true"),
+            ("is_null(if (false) { 1 })", "true"),
+            ("is_null(0)", "false"),
+            (r#"is_null("")"#, "false"),
+            ("is_null([])", "false"),
+            ("is_null()", "wrong number of arguments: expected=1, got=0"),
+            (r#"is_empty("")"#, "true"),
+            (r#"is_empty("a")"#, "false"),
+            ("is_empty([])", "true"),
+            ("is_empty([1])", "false"),
+            ("is_empty({})", "true"),
+            (r#"is_empty({"a": 1})"#, "false"),
+            (
+                "is_empty(1)",
+                "argument to `is_empty` must be STRING, ARRAY, or HASH, got 1",
+            ),
+            (
+                "is_empty(null)",
+                "argument to `is_empty` must be STRING, ARRAY, or HASH, got null",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_string_builtins() {
+        let cases = [
+            (r#"trim("  hi ")"#, "hi"),
+            (r#"trim("\t\n")"#, ""),
+            (r#"upper("aB")"#, "AB"),
+            (r#"upper("straße")"#, "STRASSE"),
+            (r#"lower("ÀB")"#, "àb"),
+            ("trim(1)", "argument to `trim` must be STRING, got 1"),
+            ("upper([])", "argument to `upper` must be STRING, got []"),
+            (
+                r#"lower("a", "b")"#,
+                "wrong number of arguments: expected=1, got=2",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_index_of_builtin() {
+        let cases = [
+            (r#"index_of("hello", "ll")"#, "2"),
+            (r#"index_of("hello", "z")"#, "-1"),
+            (r#"index_of("héllo", "l")"#, "2"),
+            (r#"index_of("abc", "")"#, "0"),
+            ("index_of([1, 2, 3], 3)", "2"),
+            (r#"index_of([1, "a", "a"], "a")"#, "1"),
+            ("index_of([], 1)", "-1"),
+            (
+                r#"index_of("abc", 1)"#,
+                "substring to `index_of` must be STRING, got 1",
+            ),
+            (
+                "index_of(1, 1)",
+                "argument to `index_of` must be STRING or ARRAY, got 1",
+            ),
+            // Functions are never compared, even within other values
+            (
+                "let f = fn() {}; index_of([1, f], f)",
+                "cannot compare functions",
+            ),
+            (
+                "let f = fn() {}; index_of([[f]], [f])",
+                "cannot compare functions",
+            ),
+            ("index_of([fn() {}, 1], 1)", "1"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_function_comparison() {
+        let cases = [
+            ("let f = fn() {}; f == f", "cannot compare functions"),
+            ("fn(x) { x } != fn(x) { x }", "cannot compare functions"),
+            ("fn() {} == 1", "cannot compare functions"),
+            ("1 != fn() {}", "cannot compare functions"),
+            (
+                "let f = fn() {}; switch (f) { f => 1, _ => 2 }",
+                "cannot compare functions",
+            ),
+            // Comparing against `null` is still allowed
+            ("fn() {} == null", "false"),
+            ("null != fn() {}", "true"),
+            ("len == len", "unknown operator: len == len"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_character_code_builtins() {
+        let cases = [
+            (r#"ord("A")"#, "65"),
+            (r#"ord("é")"#, "233"),
+            ("char(97)", "a"),
+            ("char(128018)", "🐒"),
+            (r#"char(ord("x"))"#, "x"),
+            (
+                "char(1114112)",
+                "argument to `char` is not a valid code point: 1114112",
+            ),
+            (
+                "char(55296)",
+                "argument to `char` is not a valid code point: 55296",
+            ),
+            (
+                "char(-1)",
+                "argument to `char` is not a valid code point: -1",
+            ),
+            (
+                r#"ord("ab")"#,
+                r#"argument to `ord` must be a single character, got "ab""#,
+            ),
+            (
+                r#"ord("")"#,
+                r#"argument to `ord` must be a single character, got """#,
+            ),
+            ("ord(1)", "argument to `ord` must be STRING, got 1"),
+            (r#"char("a")"#, "argument to `char` must be INTEGER, got a"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_array_literals() {
+        let cases = [("[1, 2 * 2, 3 + 3]", "[1, 4, 6]")];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_array_index_expressions() {
+        let index_cases = [
+            ("[1, 2, 3][0]", "1"),
+            ("[1, 2, 3][1]", "2"),
+            ("[1, 2, 3][2]", "3"),
+            ("let i = 0; [1][i];", "1"),
+            ("[1, 2, 3][1 + 1];", "3"),
+            ("let myArray = [1, 2, 3]; myArray[2];", "3"),
+            (
+                "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
+                "6",
+            ),
+            (
+                "let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]",
+                "2",
+            ),
+            ("[1, 2, 3][3]", "null"),
+            ("[1, 2, 3][-1]", "3"),
+            ("[1, 2, 3][-3]", "1"),
+            ("[1, 2, 3][-4]", "null"),
+            ("[][-1]", "null"),
+            ("first([1, 2, 3])", "1"),
+            ("first([])", "null"),
+            (
+                "first(1)",
+                "argument to `first` must be ARRAY or STRING, got 1",
+            ),
+            (r#"first("abc")"#, "a"),
+            (r#"first("")"#, "null"),
+            (r#"first("éa")"#, "é"),
+            ("last([1, 2, 3])", "3"),
+            ("last([])", "null"),
+            (
+                "last(1)",
+                "argument to `last` must be ARRAY or STRING, got 1",
+            ),
+            (r#"last("abc")"#, "c"),
+            (r#"last("")"#, "null"),
+            (r#"last("aé")"#, "é"),
+            ("rest([1, 2, 3])", "[2, 3]"),
+            ("rest([])", "null"),
+            ("push([], 1)", "[1]"),
+            ("push(1, 1)", "argument to `push` must be ARRAY, got 1"),
+        ];
+        check_eval_case(&index_cases);
+    }
+
+    #[test]
+    fn test_string_index_expressions() {
+        let cases = [
+            (r#""hello"[0]"#, "h"),
+            (r#""hello"[1]"#, "e"),
+            (r#""héllo"[1]"#, "é"),
+            (r#""héllo"[2]"#, "l"),
+            (r#""hello"[-1]"#, "o"),
+            (r#"let s = "héllo"; s[len(s) - 1]"#, "o"),
+            (r#""hello"[5]"#, "null"),
+            (r#""hello"[-6]"#, "null"),
+            (r#"""[0]"#, "null"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_slice_expressions() {
+        let cases = [
+            (r#""hello"[1:3]"#, "el"),
+            (r#""hello"[:2]"#, "he"),
+            (r#""hello"[2:]"#, "llo"),
+            (r#""hello"[:]"#, "hello"),
+            (r#""héllo"[1:3]"#, "él"),
+            (r#"let s = "héllo"; s[0:len(s)] == s"#, "true"),
+            (r#""hello"[-3:-1]"#, "ll"),
+            (r#""hello"[3:1]"#, ""),
+            (r#""hello"[1:100]"#, "ello"),
+            ("[1, 2, 3, 4][1:3]", "[2, 3]"),
+            ("[1, 2, 3, 4][:2]", "[1, 2]"),
+            ("[1, 2, 3, 4][2:]", "[3, 4]"),
+            ("[1, 2, 3, 4][-10:10]", "[1, 2, 3, 4]"),
+            ("[1, 2, 3, 4][3:1]", "[]"),
+            ("[1, 2, 3][true:]", "slice index must be INTEGER, got true"),
+            ("5[1:2]", "slice operator not supported: 5"),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_slice_builtin() {
+        let cases = [
+            ("slice([1, 2, 3, 4], 1, 3)", "[2, 3]"),
+            ("slice([1, 2, 3, 4], 0, 100)", "[1, 2, 3, 4]"),
+            ("slice([1, 2, 3, 4], -2, 4)", "[3, 4]"),
+            ("slice([1, 2, 3, 4], 3, 1)", "[]"),
+            ("slice([], 0, 1)", "[]"),
+            ("slice(1, 0, 1)", "argument to `slice` must be ARRAY, got 1"),
+            (
+                "slice([1], true, 1)",
+                "bounds to `slice` must be INTEGER, got true and 1",
+            ),
+            (
+                "slice([1], 0)",
+                "wrong number of arguments: expected=3, got=2",
+            ),
+        ];
+        check_eval_case(&cases);
+    }
+
+    #[test]
+    fn test_hash_literals() {
         let input = r#"
         let two = "two";
         {
@@ -789,6 +2953,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_duplicate_hash_keys() {
+        let cases = [
+            (r#"{"a": 1, "a": 2}"#, "duplicate hash key: a"),
+            (
+                r#"let k = "a"; {k: 1, "b": 2, "a": 3}"#,
+                "duplicate hash key: a",
+            ),
+            ("{1: 1, 2: 2, 1: 3}", "duplicate hash key: 1"),
+            ("{true: 1, 1 < 2: 2}", "duplicate hash key: true"),
+            (r#"{"a": 1, "b": 2}["b"]"#, "2"),
+            (r#"{1: 1, "1": 2}["1"]"#, "2"),
+        ];
+        check_eval_case(&cases);
+    }
+
     #[test]
     fn test_hash_index_expressions() {
         let cases = [
@@ -802,4 +2982,264 @@ mod tests {
         ];
         check_eval_case(&cases);
     }
+
+    #[test]
+    fn test_read_file_builtin() {
+        use crate::eval::error::ErrorKind;
+
+        let path = std::env::temp_dir().join(format!("monkey-rs-read-{}.txt", std::process::id()));
+        std::fs::write(&path, "héllo 🐒\n").unwrap();
+        let read = format!(r#"read_file("{}")"#, path.display());
+        let cases = [
+            (read.as_str(), "héllo 🐒\n"),
+            (
+                "read_file(1)",
+                "argument to `read_file` must be STRING, got 1",
+            ),
+            (
+                "read_file()",
+                "wrong number of arguments: expected=1, got=0",
+            ),
+        ];
+        check_eval_case(&cases);
+
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let missing = format!(r#"read_file("{}.missing")"#, path.display());
+        let err = eval(parse(&missing).unwrap(), &env).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+        assert!(err
+            .to_string()
+            .starts_with(&format!("could not read `{}.missing`: ", path.display())));
+
+        // A sandboxed context refuses to touch the filesystem
+        let mut ctx = context::Context::default().with_sandbox();
+        let err = eval_with_context(parse(&read).unwrap(), &env, &mut ctx).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), ErrorKind::Sandbox);
+        assert_eq!(err.to_string(), "`read_file` is disabled in the sandbox");
+        let err = eval_with_context(parse(r#"import("lib.monkey")"#).unwrap(), &env, &mut ctx)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Sandbox);
+    }
+
+    #[test]
+    fn test_write_file_builtin() {
+        use crate::eval::error::ErrorKind;
+
+        let path = std::env::temp_dir().join(format!("monkey-rs-write-{}.txt", std::process::id()));
+        // Writing replaces the existing contents of the file
+        let round_trip = format!(
+            r#"let path = "{}"; write_file(path, "old contents"); write_file(path, "héllo 🐒"); read_file(path)"#,
+            path.display()
+        );
+        let write = format!(r#"write_file("{}", "")"#, path.display());
+
+        let cases = [
+            (round_trip.as_str(), "héllo 🐒"),
+            (write.as_str(), "null"),
+            (
+                r#"write_file("out.txt", 1)"#,
+                "arguments to `write_file` must be STRING, got out.txt and 1",
+            ),
+            (
+                r#"write_file("out.txt")"#,
+                "wrong number of arguments: expected=2, got=1",
+            ),
+        ];
+        check_eval_case(&cases);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        std::fs::remove_file(&path).unwrap();
+
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let missing = format!(r#"write_file("{}", "x")"#, path.join("missing").display());
+        let err = eval(parse(&missing).unwrap(), &env).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+
+        // A sandboxed context leaves the filesystem untouched
+        let mut ctx = context::Context::default().with_sandbox();
+        let err = eval_with_context(parse(&write).unwrap(), &env, &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Sandbox);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_args_builtin() {
+        let cases = [
+            ("args()", "[]"),
+            ("args(1)", "wrong number of arguments: expected=0, got=1"),
+        ];
+        check_eval_case(&cases);
+
+        let mut ctx =
+            context::Context::default().with_args(vec!["foo".to_string(), "bar baz".to_string()]);
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let program = parse("let xs = args(); [len(xs), xs[1]]").unwrap();
+        let result = eval_with_context(program, &env, &mut ctx).expect("evaluation failed");
+        assert_eq!(result.to_string(), "[2, bar baz]");
+    }
+
+    #[test]
+    fn test_getenv_builtin() {
+        use crate::eval::error::ErrorKind;
+
+        std::env::set_var("MONKEY_RS_TEST_GETENV", "héllo 🐒");
+        let cases = [
+            (r#"getenv("MONKEY_RS_TEST_GETENV")"#, "héllo 🐒"),
+            (r#"getenv("MONKEY_RS_TEST_GETENV_UNSET")"#, "null"),
+            ("getenv(1)", "argument to `getenv` must be STRING, got 1"),
+            ("getenv()", "wrong number of arguments: expected=1, got=0"),
+        ];
+        check_eval_case(&cases);
+
+        // A sandboxed context keeps the host environment hidden
+        let mut ctx = context::Context::default().with_sandbox();
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let program = parse(r#"getenv("MONKEY_RS_TEST_GETENV")"#).unwrap();
+        let err = eval_with_context(program, &env, &mut ctx).unwrap_err();
+        std::env::remove_var("MONKEY_RS_TEST_GETENV");
+        assert_eq!(err.kind(), ErrorKind::Sandbox);
+    }
+
+    #[test]
+    fn test_now_builtin() {
+        use crate::eval::error::ErrorKind;
+
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let result = eval(
+            parse("let start = now(); [start, now() - start]").unwrap(),
+            &env,
+        );
+        match &*result.expect("evaluation failed") {
+            object::Object::Array(times) => match (&*times[0], &*times[1]) {
+                (object::Object::Integer(start), object::Object::Integer(elapsed)) => {
+                    assert!(*start > 0);
+                    assert!(*elapsed >= 0);
+                }
+                other => panic!("expected integers, got {:?}", other),
+            },
+            other => panic!("expected an array, got {}", other),
+        }
+
+        let cases = [("now(1)", "wrong number of arguments: expected=0, got=1")];
+        check_eval_case(&cases);
+
+        // A sandboxed context keeps evaluation deterministic
+        let mut ctx = context::Context::default().with_sandbox();
+        let err = eval_with_context(parse("now()").unwrap(), &env, &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Sandbox);
+    }
+
+    #[test]
+    fn test_rand_builtin() {
+        use crate::eval::error::ErrorKind;
+
+        // The same seed draws the same numbers, each within the bound
+        let draw = |seed| {
+            let mut ctx = context::Context::default().with_seed(seed);
+            let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+            let program = parse("[rand(10), rand(10), rand(10), rand(10), rand(1000000)]");
+            let result = eval_with_context(program.unwrap(), &env, &mut ctx).unwrap();
+            match &*result {
+                object::Object::Array(numbers) => numbers
+                    .iter()
+                    .map(|n| match **n {
+                        object::Object::Integer(n) => n,
+                        ref other => panic!("expected an integer, got {}", other),
+                    })
+                    .collect::<Vec<i64>>(),
+                other => panic!("expected an array, got {}", other),
+            }
+        };
+        let numbers = draw(42);
+        assert_eq!(numbers, draw(42));
+        assert_ne!(numbers, draw(43));
+        assert!(numbers[..4].iter().all(|n| (0..10).contains(n)));
+        assert!((0..1000000).contains(&numbers[4]));
+
+        let cases = [
+            ("rand(1)", "0"),
+            ("rand(0)", "argument to `rand` must be positive, got 0"),
+            ("rand(true)", "argument to `rand` must be INTEGER, got true"),
+            ("rand()", "wrong number of arguments: expected=1, got=0"),
+        ];
+        check_eval_case(&cases);
+
+        let mut ctx = context::Context::default().with_sandbox();
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let err = eval_with_context(parse("rand(10)").unwrap(), &env, &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Sandbox);
+    }
+
+    #[test]
+    fn test_shuffle_builtin() {
+        use crate::eval::error::ErrorKind;
+
+        let shuffle = |seed| {
+            let mut ctx = context::Context::default().with_seed(seed);
+            let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+            let program = parse("let xs = [1, 2, 3, 4, 5, 6, 7, 8]; [shuffle(xs), xs]");
+            let result = eval_with_context(program.unwrap(), &env, &mut ctx).unwrap();
+            result.to_string()
+        };
+        // The same seed yields the same order, and the given array is unchanged
+        let shuffled = shuffle(7);
+        assert_eq!(shuffled, shuffle(7));
+        assert_ne!(shuffled, shuffle(8));
+        assert!(shuffled.ends_with(", [1, 2, 3, 4, 5, 6, 7, 8]]"));
+
+        // Shuffling preserves the elements and their counts
+        let mut ctx = context::Context::default().with_seed(7);
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+        let program = parse(r#"shuffle([1, 1, "a", [2], true, 1])"#).unwrap();
+        let result = eval_with_context(program, &env, &mut ctx).unwrap();
+        match &*result {
+            object::Object::Array(elements) => {
+                let mut elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                elements.sort();
+                assert_eq!(elements, ["1", "1", "1", "[2]", "a", "true"]);
+            }
+            other => panic!("expected an array, got {}", other),
+        }
+
+        let cases = [
+            ("shuffle([])", "[]"),
+            ("shuffle([1])", "[1]"),
+            (
+                r#"shuffle("abc")"#,
+                "argument to `shuffle` must be ARRAY, got abc",
+            ),
+            ("shuffle()", "wrong number of arguments: expected=1, got=0"),
+        ];
+        check_eval_case(&cases);
+
+        let mut ctx = context::Context::default().with_sandbox();
+        let err = eval_with_context(parse("shuffle([1, 2])").unwrap(), &env, &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Sandbox);
+    }
+
+    #[test]
+    fn test_puts_writes_to_context_output() {
+        let buffer = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let mut ctx = context::Context::new(buffer.clone());
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+
+        let node = parse("puts(1); puts(2)").expect("failed to parse input");
+        let result = eval_with_context(node, &env, &mut ctx).expect("evaluation failed");
+
+        assert_eq!(*result, object::Object::Null);
+        assert_eq!(buffer.borrow().as_slice(), b"1\n2\n");
+    }
+
+    #[test]
+    fn test_println_joins_arguments() {
+        let buffer = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let mut ctx = context::Context::new(buffer.clone());
+        let env: environment::Env = Rc::new(RefCell::new(Default::default()));
+
+        let node = parse(r#"println("a", 1, [true, null]); println()"#).unwrap();
+        let result = eval_with_context(node, &env, &mut ctx).expect("evaluation failed");
+
+        assert_eq!(*result, object::Object::Null);
+        assert_eq!(buffer.borrow().as_slice(), b"a 1 [true, null]\n\n");
+    }
 }
@@ -1,18 +1,31 @@
 //! Built-in functions to Monkey
 
-use std::{fmt, rc::Rc};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    rc::Rc,
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+use super::context;
+use super::environment;
 use super::error;
 use super::object;
+use crate::parser;
+use crate::symbol::{Symbol, SymbolMap};
 
 /// Built-in function provided by Monkey.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Builtin {
-    /// Return the length of an iterable Monkey object.
+    /// Return the length of an iterable Monkey object, or the number of entries
+    /// of a hash.
     Len,
-    /// Return the first element of a given array.
+    /// Return the first element of a given array, or the first character of
+    /// a given string.
     First,
-    /// Return the last element of a given array.
+    /// Return the last element of a given array, or the last character of a
+    /// given string.
     Last,
     /// Return a new array containing all the elements of the array passed as
     /// argument, except for the first one
@@ -20,8 +33,89 @@ pub enum Builtin {
     /// Allocates a new array with the same elements as the array passed as
     /// argument with the addition of the new, pushed element.
     Push,
-    /// Prints the given arguments to STDOUT
+    /// Prints the given arguments to the context's output, one per line.
     Puts,
+    /// Return a new array containing the elements of the array passed as
+    /// argument within the half-open `[start, end)` range.
+    Slice,
+    /// Return whether the given object is `null`.
+    IsNull,
+    /// Return whether the given string, array, or hash has a length of zero.
+    IsEmpty,
+    /// Read, parse, and evaluate another Monkey source file into the caller's
+    /// environment, returning the value of its last statement.
+    Import,
+    /// Return a new string with the leading and trailing whitespace of the
+    /// given string removed.
+    Trim,
+    /// Return a new string with the characters of the given string converted
+    /// to uppercase, as defined by Unicode.
+    Upper,
+    /// Return a new string with the characters of the given string converted
+    /// to lowercase, as defined by Unicode.
+    Lower,
+    /// Return the character index of the first occurrence of a substring in a
+    /// given string, or the index of the first element of a given array equal
+    /// to a value, or `-1` if there is none.
+    IndexOf,
+    /// Return the Unicode scalar value of the given one-character string.
+    Ord,
+    /// Return the one-character string of the given Unicode scalar value.
+    Char,
+    /// Return a deep copy of the given array or hash, or the given object
+    /// itself if it is not a collection.
+    Clone,
+    /// Return the entries of the given hash as an array of `[key, value]`
+    /// pairs, sorted by the displayed form of the keys.
+    Entries,
+    /// Return the given template string with each `{}` placeholder replaced,
+    /// in order, by the displayed form of the next argument.
+    Format,
+    /// Return the characters of the given string as one-character strings, or
+    /// the entries of the given hash as `entries` would. Arrays are returned
+    /// as is.
+    ToArray,
+    /// Parse and evaluate the given string of Monkey source code in the
+    /// caller's environment, returning the value of its last statement.
+    Eval,
+    /// Return the sum of the integers of the given array.
+    Sum,
+    /// Return a new array joining the elements of the given arrays, or of the
+    /// arrays of a single given array, in order.
+    Concat,
+    /// Return an array of `[a, b]` pairs of the elements at the same index of
+    /// the two given arrays, as long as the shorter array.
+    Zip,
+    /// Prints the given arguments to the context's output on a single line,
+    /// separated by spaces.
+    Println,
+    /// Return a new hash with the entries of all the given hashes, where the
+    /// value of a later hash wins when the same key is in more than one.
+    Merge,
+    /// Return the value of the given key in the given hash, or the given
+    /// default if the hash does not contain the key.
+    Get,
+    /// Return a new hash with the entries of the given hash and the given key
+    /// set to the given value, leaving the given hash unchanged.
+    Set,
+    /// Return the contents of the file at the given path as a string.
+    ReadFile,
+    /// Write the given string to the file at the given path, replacing any
+    /// existing contents.
+    WriteFile,
+    /// Return the arguments passed to the program as an array of strings.
+    Args,
+    /// Return the value of the given environment variable of the host process,
+    /// or `null` if it is not set.
+    GetEnv,
+    /// Return the current Unix time in milliseconds.
+    Now,
+    /// Return a pseudo-random integer in the range `[0, n)` for the given
+    /// `n`, drawn from the context's generator.
+    Rand,
+    /// Return a new array with the elements of the given array in a
+    /// pseudo-random order, drawn from the context's generator.
+    Shuffle,
 }
 
 impl fmt::Display for Builtin {
@@ -33,29 +127,108 @@ impl fmt::Display for Builtin {
             Builtin::Rest => write!(f, "rest"),
             Builtin::Push => write!(f, "push"),
             Builtin::Puts => write!(f, "puts"),
+            Builtin::Slice => write!(f, "slice"),
+            Builtin::IsNull => write!(f, "is_null"),
+            Builtin::IsEmpty => write!(f, "is_empty"),
+            Builtin::Import => write!(f, "import"),
+            Builtin::Trim => write!(f, "trim"),
+            Builtin::Upper => write!(f, "upper"),
+            Builtin::Lower => write!(f, "lower"),
+            Builtin::IndexOf => write!(f, "index_of"),
+            Builtin::Ord => write!(f, "ord"),
+            Builtin::Char => write!(f, "char"),
+            Builtin::Clone => write!(f, "clone"),
+            Builtin::Entries => write!(f, "entries"),
+            Builtin::Format => write!(f, "format"),
+            Builtin::ToArray => write!(f, "to_array"),
+            Builtin::Eval => write!(f, "eval"),
+            Builtin::Sum => write!(f, "sum"),
+            Builtin::Concat => write!(f, "concat"),
+            Builtin::Zip => write!(f, "zip"),
+            Builtin::Println => write!(f, "println"),
+            Builtin::Merge => write!(f, "merge"),
+            Builtin::Get => write!(f, "get"),
+            Builtin::Set => write!(f, "set"),
+            Builtin::ReadFile => write!(f, "read_file"),
+            Builtin::WriteFile => write!(f, "write_file"),
+            Builtin::Args => write!(f, "args"),
+            Builtin::GetEnv => write!(f, "getenv"),
+            Builtin::Now => write!(f, "now"),
+            Builtin::Rand => write!(f, "rand"),
+            Builtin::Shuffle => write!(f, "shuffle"),
         }
     }
 }
 
 impl Builtin {
+    /// Every builtin function.
+    pub const ALL: &[Builtin] = &[
+        Builtin::Len,
+        Builtin::First,
+        Builtin::Last,
+        Builtin::Rest,
+        Builtin::Push,
+        Builtin::Puts,
+        Builtin::Slice,
+        Builtin::IsNull,
+        Builtin::IsEmpty,
+        Builtin::Import,
+        Builtin::Trim,
+        Builtin::Upper,
+        Builtin::Lower,
+        Builtin::IndexOf,
+        Builtin::Ord,
+        Builtin::Char,
+        Builtin::Clone,
+        Builtin::Entries,
+        Builtin::Format,
+        Builtin::ToArray,
+        Builtin::Eval,
+        Builtin::Sum,
+        Builtin::Concat,
+        Builtin::Zip,
+        Builtin::Println,
+        Builtin::Merge,
+        Builtin::Get,
+        Builtin::Set,
+        Builtin::ReadFile,
+        Builtin::WriteFile,
+        Builtin::Args,
+        Builtin::GetEnv,
+        Builtin::Now,
+        Builtin::Rand,
+        Builtin::Shuffle,
+    ];
+
     /// Lookup and retrieve a builtin function object by name/ identifier, if it
     /// exists.
     pub fn lookup(name: &str) -> Option<object::Object> {
-        match name {
-            "len" => Some(object::Object::Builtin(Builtin::Len)),
-            "first" => Some(object::Object::Builtin(Builtin::First)),
-            "last" => Some(object::Object::Builtin(Builtin::Last)),
-            "rest" => Some(object::Object::Builtin(Builtin::Rest)),
-            "push" => Some(object::Object::Builtin(Builtin::Push)),
-            "puts" => Some(object::Object::Builtin(Builtin::Puts)),
-            _ => None,
-        }
+        // Builtin names are interned along with the table, so a name that was
+        // never interned cannot name a builtin
+        let builtins = builtins();
+        Symbol::get(name).and_then(|name| builtins.get(&name).cloned().map(object::Object::Builtin))
     }
 
-    /// Apply the builtin function on the passed arguments slice.
+    /// Lookup and retrieve a builtin function object by interned name, if it
+    /// exists.
+    pub fn lookup_symbol(name: Symbol) -> Option<object::Object> {
+        builtins().get(&name).cloned().map(object::Object::Builtin)
+    }
+
+    /// Apply the builtin function on the passed arguments slice within the
+    /// caller's environment and the given evaluation context.
+    ///
+    /// Builtins may read and bind names in the environment, e.g., `import` and
+    /// `eval` evaluate source code into it. Higher-order builtins call back into
+    /// evaluation to apply a function argument with `super::apply_function`,
+    /// passing the environment and context along so that calls made on their
+    /// behalf are evaluated, and counted against the step limit, like any
+    /// other.
     pub fn apply(
         &self,
         args: &[Rc<object::Object>],
+        env: &environment::Env,
+        ctx: &mut context::Context,
     ) -> Result<Rc<object::Object>, error::EvaluationError> {
         match self {
             Builtin::Len => {
@@ -63,12 +236,15 @@ impl Builtin {
 
                 match &*args[0] {
                     object::Object::String(str) => {
-                        Ok(Rc::new(object::Object::Integer(str.len() as i64)))
+                        Ok(Rc::new(object::Object::Integer(str.chars().count() as i64)))
                     }
                     object::Object::Array(arr) => {
                         Ok(Rc::new(object::Object::Integer(arr.len() as i64)))
                     }
-                    other => Err(error::EvaluationError::new(format!(
+                    object::Object::Hash(hash) => {
+                        Ok(Rc::new(object::Object::Integer(hash.len() as i64)))
+                    }
+                    other => Err(error::EvaluationError::type_mismatch(format!(
                         "argument to `len` not supported, got {}",
                         other
                     ))),
@@ -82,8 +258,12 @@ impl Builtin {
                         Some(element) => Ok(Rc::clone(element)),
                         None => Ok(Rc::new(object::Object::Null)),
                     },
-                    other => Err(error::EvaluationError::new(format!(
-                        "argument to `first` must be ARRAY, got {}",
+                    object::Object::String(str) => match str.chars().next() {
+                        Some(c) => Ok(Rc::new(object::Object::String(c.to_string()))),
+                        None => Ok(Rc::new(object::Object::Null)),
+                    },
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `first` must be ARRAY or STRING, got {}",
                         other
                     ))),
                 }
@@ -96,8 +276,12 @@ impl Builtin {
                         Some(element) => Ok(Rc::clone(element)),
                         None => Ok(Rc::new(object::Object::Null)),
                     },
-                    other => Err(error::EvaluationError::new(format!(
-                        "argument to `last` must be ARRAY, got {}",
+                    object::Object::String(str) => match str.chars().next_back() {
+                        Some(c) => Ok(Rc::new(object::Object::String(c.to_string()))),
+                        None => Ok(Rc::new(object::Object::Null)),
+                    },
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `last` must be ARRAY or STRING, got {}",
                         other
                     ))),
                 }
@@ -115,7 +299,7 @@ impl Builtin {
                             Ok(Rc::new(object::Object::Null))
                         }
                     }
-                    other => Err(error::EvaluationError::new(format!(
+                    other => Err(error::EvaluationError::type_mismatch(format!(
                         "argument to `rest` must be ARRAY, got {}",
                         other
                     ))),
@@ -130,27 +314,664 @@ impl Builtin {
                         new_elements.push(Rc::clone(&args[1]));
                         Ok(Rc::new(object::Object::Array(new_elements)))
                     }
-                    other => Err(error::EvaluationError::new(format!(
+                    other => Err(error::EvaluationError::type_mismatch(format!(
                         "argument to `push` must be ARRAY, got {}",
                         other
                     ))),
                 }
             }
             Builtin::Puts => {
-                args.iter().for_each(|obj| println!("{}", obj));
+                let output = ctx.output();
+                let mut output = output.borrow_mut();
+                for obj in args {
+                    writeln!(output, "{}", obj).map_err(|e| {
+                        error::EvaluationError::io(format!("failed to write output: {}", e))
+                    })?;
+                }
 
                 // Puts returns a null value
                 Ok(Rc::new(object::Object::Null))
             }
+            Builtin::Println => {
+                let line = args
+                    .iter()
+                    .map(|obj| obj.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                writeln!(ctx.output().borrow_mut(), "{}", line).map_err(|e| {
+                    error::EvaluationError::io(format!("failed to write output: {}", e))
+                })?;
+
+                Ok(Rc::new(object::Object::Null))
+            }
+            Builtin::Slice => {
+                check_args_count(3, args.len())?;
+
+                let (start, end) = match (&*args[1], &*args[2]) {
+                    (object::Object::Integer(start), object::Object::Integer(end)) => {
+                        (*start, *end)
+                    }
+                    (start, end) => {
+                        return Err(error::EvaluationError::type_mismatch(format!(
+                            "bounds to `slice` must be INTEGER, got {} and {}",
+                            start, end
+                        )))
+                    }
+                };
+
+                match &*args[0] {
+                    object::Object::Array(arr) => {
+                        let (start, end) = super::resolve_slice(Some(start), Some(end), arr.len());
+                        Ok(Rc::new(object::Object::Array(arr[start..end].to_vec())))
+                    }
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `slice` must be ARRAY, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::IsNull => {
+                check_args_count(1, args.len())?;
+
+                Ok(Rc::new(object::Object::Boolean(matches!(
+                    &*args[0],
+                    object::Object::Null
+                ))))
+            }
+            Builtin::IsEmpty => {
+                check_args_count(1, args.len())?;
+
+                let empty = match &*args[0] {
+                    object::Object::String(str) => str.is_empty(),
+                    object::Object::Array(arr) => arr.is_empty(),
+                    object::Object::Hash(hash) => hash.is_empty(),
+                    other => {
+                        return Err(error::EvaluationError::type_mismatch(format!(
+                            "argument to `is_empty` must be STRING, ARRAY, or HASH, got {}",
+                            other
+                        )))
+                    }
+                };
+                Ok(Rc::new(object::Object::Boolean(empty)))
+            }
+            Builtin::Import => {
+                check_args_count(1, args.len())?;
+
+                ctx.check_sandbox("import")?;
+
+                match &*args[0] {
+                    object::Object::String(path) => import(path, env, ctx),
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `import` must be STRING, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Trim | Builtin::Upper | Builtin::Lower => {
+                check_args_count(1, args.len())?;
+
+                match &*args[0] {
+                    object::Object::String(str) => {
+                        let result = match self {
+                            Builtin::Trim => str.trim().to_string(),
+                            Builtin::Upper => str.to_uppercase(),
+                            _ => str.to_lowercase(),
+                        };
+                        Ok(Rc::new(object::Object::String(result)))
+                    }
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `{}` must be STRING, got {}",
+                        self, other
+                    ))),
+                }
+            }
+            Builtin::IndexOf => {
+                check_args_count(2, args.len())?;
+
+                let index = match (&*args[0], &*args[1]) {
+                    // Convert the byte offset of the match to a character index
+                    (object::Object::String(str), object::Object::String(substr)) => str
+                        .find(substr.as_str())
+                        .map(|offset| str[..offset].chars().count()),
+                    (object::Object::String(_), other) => {
+                        return Err(error::EvaluationError::type_mismatch(format!(
+                            "substring to `index_of` must be STRING, got {}",
+                            other
+                        )))
+                    }
+                    // Functions close over environments, so comparing them
+                    // would compare whole environments
+                    (object::Object::Array(_), target) if contains_function(target) => {
+                        return Err(error::EvaluationError::type_mismatch(
+                            "cannot compare functions".to_string(),
+                        ))
+                    }
+                    (object::Object::Array(arr), target) => {
+                        arr.iter().position(|element| **element == *target)
+                    }
+                    (other, _) => {
+                        return Err(error::EvaluationError::type_mismatch(format!(
+                            "argument to `index_of` must be STRING or ARRAY, got {}",
+                            other
+                        )))
+                    }
+                };
+                Ok(Rc::new(object::Object::Integer(
+                    index.map_or(-1, |index| index as i64),
+                )))
+            }
+            Builtin::Ord => {
+                check_args_count(1, args.len())?;
+
+                match &*args[0] {
+                    object::Object::String(str) => {
+                        let mut chars = str.chars();
+                        match (chars.next(), chars.next()) {
+                            (Some(c), None) => Ok(Rc::new(object::Object::Integer(c as i64))),
+                            _ => Err(error::EvaluationError::invalid_argument(format!(
+                                "argument to `ord` must be a single character, got \"{}\"",
+                                str
+                            ))),
+                        }
+                    }
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `ord` must be STRING, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Char => {
+                check_args_count(1, args.len())?;
+
+                match &*args[0] {
+                    object::Object::Integer(code) => u32::try_from(*code)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .map(|c| Rc::new(object::Object::String(c.to_string())))
+                        .ok_or_else(|| {
+                            error::EvaluationError::invalid_argument(format!(
+                                "argument to `char` is not a valid code point: {}",
+                                code
+                            ))
+                        }),
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `char` must be INTEGER, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Clone => {
+                check_args_count(1, args.len())?;
+
+                Ok(object::Object::deep_copy(&args[0]))
+            }
+            Builtin::Entries => {
+                check_args_count(1, args.len())?;
+
+                match &*args[0] {
+                    object::Object::Hash(hash) => Ok(Rc::new(object::Object::Array(entries(hash)))),
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `entries` must be HASH, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Merge => {
+                if args.len() < 2 {
+                    return Err(error::EvaluationError::wrong_arg_count(format!(
+                        "wrong number of arguments: expected at least 2, got={}",
+                        args.len()
+                    )));
+                }
+
+                // Later hashes overwrite the values of keys already merged
+                let mut merged = HashMap::new();
+                for arg in args {
+                    match &**arg {
+                        object::Object::Hash(hash) => merged.extend(
+                            hash.iter()
+                                .map(|(key, value)| (Rc::clone(key), Rc::clone(value))),
+                        ),
+                        other => {
+                            return Err(error::EvaluationError::type_mismatch(format!(
+                                "arguments to `merge` must be HASH, got {}",
+                                other
+                            )))
+                        }
+                    }
+                }
+
+                Ok(Rc::new(object::Object::Hash(merged)))
+            }
+            Builtin::Get => {
+                check_args_count(3, args.len())?;
+
+                match &*args[0] {
+                    object::Object::Hash(hash) => {
+                        let key = hash_key(&args[1])?;
+                        Ok(Rc::clone(hash.get(&key).unwrap_or(&args[2])))
+                    }
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `get` must be HASH, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Set => {
+                check_args_count(3, args.len())?;
+
+                match &*args[0] {
+                    object::Object::Hash(hash) => {
+                        let mut hash = hash.clone();
+                        hash.insert(Rc::new(hash_key(&args[1])?), Rc::clone(&args[2]));
+                        Ok(Rc::new(object::Object::Hash(hash)))
+                    }
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `set` must be HASH, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::ReadFile => {
+                check_args_count(1, args.len())?;
+                ctx.check_sandbox("read_file")?;
+
+                match &*args[0] {
+                    object::Object::String(path) => fs::read_to_string(path)
+                        .map(|contents| Rc::new(object::Object::String(contents)))
+                        .map_err(|e| {
+                            error::EvaluationError::io(format!("could not read `{}`: {}", path, e))
+                        }),
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `read_file` must be STRING, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::WriteFile => {
+                check_args_count(2, args.len())?;
+                ctx.check_sandbox("write_file")?;
+
+                match (&*args[0], &*args[1]) {
+                    (object::Object::String(path), object::Object::String(contents)) => {
+                        fs::write(path, contents).map_err(|e| {
+                            error::EvaluationError::io(format!("could not write `{}`: {}", path, e))
+                        })?;
+                        Ok(Rc::new(object::Object::Null))
+                    }
+                    (path, contents) => Err(error::EvaluationError::type_mismatch(format!(
+                        "arguments to `write_file` must be STRING, got {} and {}",
+                        path, contents
+                    ))),
+                }
+            }
+            Builtin::Args => {
+                check_args_count(0, args.len())?;
+
+                Ok(Rc::new(object::Object::Array(
+                    ctx.args()
+                        .iter()
+                        .map(|arg| Rc::new(object::Object::String(arg.clone())))
+                        .collect(),
+                )))
+            }
+            Builtin::GetEnv => {
+                check_args_count(1, args.len())?;
+                ctx.check_sandbox("getenv")?;
+
+                match &*args[0] {
+                    object::Object::String(name) => match std::env::var(name) {
+                        Ok(value) => Ok(Rc::new(object::Object::String(value))),
+                        Err(std::env::VarError::NotPresent) => Ok(Rc::new(object::Object::Null)),
+                        Err(std::env::VarError::NotUnicode(_)) => {
+                            Err(error::EvaluationError::invalid_argument(format!(
+                                "environment variable `{}` is not valid UTF-8",
+                                name
+                            )))
+                        }
+                    },
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `getenv` must be STRING, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Now => {
+                check_args_count(0, args.len())?;
+                ctx.check_sandbox("now")?;
+
+                let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| {
+                    error::EvaluationError::io(format!("could not read the system clock: {}", e))
+                })?;
+                // Saturate rather than fail in the far future
+                let millis = i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX);
+                Ok(Rc::new(object::Object::Integer(millis)))
+            }
+            Builtin::Rand => {
+                check_args_count(1, args.len())?;
+                ctx.check_sandbox("rand")?;
+
+                match &*args[0] {
+                    object::Object::Integer(bound) if *bound > 0 => Ok(Rc::new(
+                        object::Object::Integer(ctx.random().below(*bound as u64) as i64),
+                    )),
+                    object::Object::Integer(bound) => {
+                        Err(error::EvaluationError::invalid_argument(format!(
+                            "argument to `rand` must be positive, got {}",
+                            bound
+                        )))
+                    }
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `rand` must be INTEGER, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Shuffle => {
+                check_args_count(1, args.len())?;
+                ctx.check_sandbox("shuffle")?;
+
+                match &*args[0] {
+                    object::Object::Array(arr) => {
+                        // Fisher-Yates: swap each element with a random one
+                        // at or before it
+                        let mut arr = arr.clone();
+                        for i in (1..arr.len()).rev() {
+                            let j = ctx.random().below(i as u64 + 1) as usize;
+                            arr.swap(i, j);
+                        }
+                        Ok(Rc::new(object::Object::Array(arr)))
+                    }
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `shuffle` must be ARRAY, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::ToArray => {
+                check_args_count(1, args.len())?;
+
+                match &*args[0] {
+                    object::Object::Array(_) => Ok(Rc::clone(&args[0])),
+                    object::Object::String(str) => Ok(Rc::new(object::Object::Array(
+                        str.chars()
+                            .map(|c| Rc::new(object::Object::String(c.to_string())))
+                            .collect(),
+                    ))),
+                    object::Object::Hash(hash) => Ok(Rc::new(object::Object::Array(entries(hash)))),
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `to_array` must be STRING, ARRAY, or HASH, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Eval => {
+                check_args_count(1, args.len())?;
+
+                match &*args[0] {
+                    object::Object::String(source) => eval(source, env, ctx),
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `eval` must be STRING, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Sum => {
+                check_args_count(1, args.len())?;
+
+                match &*args[0] {
+                    object::Object::Array(arr) => {
+                        let mut sum: i64 = 0;
+                        for element in arr {
+                            let int = match **element {
+                                object::Object::Integer(int) => int,
+                                ref other => {
+                                    return Err(error::EvaluationError::type_mismatch(format!(
+                                        "elements of argument to `sum` must be INTEGER, got {}",
+                                        other
+                                    )))
+                                }
+                            };
+                            sum = sum.checked_add(int).ok_or_else(|| {
+                                error::EvaluationError::overflow(format!(
+                                    "integer overflow: {} + {}",
+                                    sum, int
+                                ))
+                            })?;
+                        }
+                        Ok(Rc::new(object::Object::Integer(sum)))
+                    }
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "argument to `sum` must be ARRAY, got {}",
+                        other
+                    ))),
+                }
+            }
+            Builtin::Concat => {
+                let arrays = match args {
+                    [] => {
+                        return Err(error::EvaluationError::wrong_arg_count(
+                            "wrong number of arguments: expected at least 1, got=0".to_string(),
+                        ))
+                    }
+                    // A single array holds the arrays to join
+                    [arg] => match &**arg {
+                        object::Object::Array(arr) => arr,
+                        other => {
+                            return Err(error::EvaluationError::type_mismatch(format!(
+                                "argument to `concat` must be ARRAY, got {}",
+                                other
+                            )))
+                        }
+                    },
+                    args => args,
+                };
+
+                let mut joined = Vec::new();
+                for array in arrays {
+                    match &**array {
+                        object::Object::Array(arr) => joined.extend(arr.iter().cloned()),
+                        other => {
+                            return Err(error::EvaluationError::type_mismatch(format!(
+                                "arrays to `concat` must be ARRAY, got {}",
+                                other
+                            )))
+                        }
+                    }
+                }
+                Ok(Rc::new(object::Object::Array(joined)))
+            }
+            Builtin::Zip => {
+                check_args_count(2, args.len())?;
+
+                match (&*args[0], &*args[1]) {
+                    (object::Object::Array(left), object::Object::Array(right)) => {
+                        Ok(Rc::new(object::Object::Array(
+                            left.iter()
+                                .zip(right)
+                                .map(|(a, b)| {
+                                    Rc::new(object::Object::Array(vec![Rc::clone(a), Rc::clone(b)]))
+                                })
+                                .collect(),
+                        )))
+                    }
+                    (left, right) => Err(error::EvaluationError::type_mismatch(format!(
+                        "arguments to `zip` must be ARRAY, got {} and {}",
+                        left, right
+                    ))),
+                }
+            }
+            Builtin::Format => match args.split_first() {
+                Some((template, values)) => match &**template {
+                    object::Object::String(template) => format(template, values),
+                    other => Err(error::EvaluationError::type_mismatch(format!(
+                        "template to `format` must be STRING, got {}",
+                        other
+                    ))),
+                },
+                None => Err(error::EvaluationError::wrong_arg_count(
+                    "wrong number of arguments: expected at least 1, got=0".to_string(),
+                )),
+            },
+        }
+    }
+}
+
+/// Returns the table of builtin functions by name, which is built once on
+/// first use.
+fn builtins() -> &'static SymbolMap<Builtin> {
+    static BUILTINS: OnceLock<SymbolMap<Builtin>> = OnceLock::new();
+    BUILTINS.get_or_init(|| {
+        Builtin::ALL
+            .iter()
+            .map(|builtin| (Symbol::intern(&builtin.to_string()), builtin.clone()))
+            .collect()
+    })
+}
+
+/// Read, parse, and evaluate the Monkey source file at the given path into the
+/// environment. Relative paths are resolved against the directory of the
+/// importing file.
+fn import(
+    path: &str,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let resolved = ctx
+        .base_dir()
+        .join(path)
+        .canonicalize()
+        .map_err(|e| error::EvaluationError::io(format!("could not read `{}`: {}", path, e)))?;
+
+    // Importing a file that is still being evaluated would never terminate
+    if ctx.is_evaluating(&resolved) {
+        return Err(error::EvaluationError::import(format!(
+            "import cycle detected: `{}`",
+            path
+        )));
+    }
+
+    let input = fs::read_to_string(&resolved)
+        .map_err(|e| error::EvaluationError::io(format!("could not read `{}`: {}", path, e)))?;
+    let program = parser::parse(&input).map_err(|e| {
+        error::EvaluationError::import(format!("could not parse `{}`: {}", path, e))
+    })?;
+
+    ctx.push_file(resolved);
+    // Positions within the imported file would be misleading when reported
+    // against the importing file, so report the error at the import instead
+    let evaluated = super::eval_with_context(program, env, ctx)
+        .map_err(|e| error::EvaluationError::new(e.kind(), e.to_string()));
+    ctx.pop_file();
+
+    super::unwrap_return_value(evaluated?)
+}
+
+/// Parse and evaluate the Monkey source code in the environment, returning the
+/// value of its last statement.
+fn eval(
+    source: &str,
+    env: &environment::Env,
+    ctx: &mut context::Context,
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let program = parser::parse(source).map_err(|e| {
+        error::EvaluationError::invalid_argument(format!(
+            "could not parse argument to `eval`: {}",
+            e
+        ))
+    })?;
+
+    // Positions within the source would be misleading when reported against
+    // the calling program, so report the error at the call instead
+    let evaluated = super::eval_with_context(program, env, ctx)
+        .map_err(|e| error::EvaluationError::new(e.kind(), e.to_string()))?;
+    super::unwrap_return_value(evaluated)
+}
+
+/// Returns whether the object is a function or a collection containing one.
+pub(super) fn contains_function(obj: &object::Object) -> bool {
+    match obj {
+        object::Object::Function(..) => true,
+        object::Object::Array(arr) => arr.iter().any(|element| contains_function(element)),
+        object::Object::Hash(hash) => hash.values().any(|value| contains_function(value)),
+        _ => false,
+    }
+}
+
+/// Converts the object into a hash key, returning an error if the object
+/// cannot be used as one.
+fn hash_key(key: &object::Object) -> Result<object::HashableObject, error::EvaluationError> {
+    key.as_hashable().ok_or_else(|| {
+        error::EvaluationError::type_mismatch(format!("unusable as hash key: {}", key))
+    })
+}
+
+/// Returns the entries of the hash as `[key, value]` arrays, sorted by the
+/// displayed form of the keys.
+pub(super) fn entries(
+    hash: &HashMap<Rc<object::HashableObject>, Rc<object::Object>>,
+) -> Vec<Rc<object::Object>> {
+    // Hash iteration order is unspecified, so sort the entries to keep the
+    // result deterministic
+    let mut entries = hash.iter().collect::<Vec<_>>();
+    entries.sort_by_cached_key(|(key, _)| key.to_string());
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            let key = Rc::new(object::Object::from(&**key));
+            Rc::new(object::Object::Array(vec![key, Rc::clone(value)]))
+        })
+        .collect()
+}
+
+/// Substitute the displayed form of the values, in order, for the `{}`
+/// placeholders of the template. `{{` and `}}` stand for literal braces.
+fn format(
+    template: &str,
+    values: &[Rc<object::Object>],
+) -> Result<Rc<object::Object>, error::EvaluationError> {
+    let mut result = String::with_capacity(template.len());
+    let mut placeholders = 0;
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                result.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                if let Some(value) = values.get(placeholders) {
+                    result += &value.to_string();
+                }
+                placeholders += 1;
+            }
+            ('{', _) | ('}', _) => {
+                return Err(error::EvaluationError::invalid_argument(format!(
+                    "unmatched `{}` in format string, use `{}{}` for a literal brace",
+                    c, c, c
+                )))
+            }
+            _ => result.push(c),
         }
     }
+
+    if placeholders != values.len() {
+        return Err(error::EvaluationError::wrong_arg_count(format!(
+            "wrong number of arguments to `format`: template has {} placeholder(s), got {}",
+            placeholders,
+            values.len()
+        )));
+    }
+    Ok(Rc::new(object::Object::String(result)))
 }
 
 /// Verify that the number of arguments passed matches expected count.
 fn check_args_count(expected: usize, actual: usize) -> Result<(), error::EvaluationError> {
     match expected == actual {
         true => Ok(()),
-        false => Err(error::EvaluationError::new(format!(
+        false => Err(error::EvaluationError::wrong_arg_count(format!(
             "wrong number of arguments: expected={}, got={}",
             expected, actual
         ))),
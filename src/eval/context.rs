@@ -0,0 +1,208 @@
+/*!
+# Context
+
+Defines the evaluation `Context`, which holds the state of an evaluation that
+lives outside of the program environment, e.g., the writer that builtins such
+as `puts` emit their output to, the source files currently being imported, the
+budget of evaluation steps, the arguments passed to the program, the state of
+the pseudo-random number generator, and whether builtins may access the host
+system.
+*/
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use super::error::EvaluationError;
+use super::random::Random;
+
+/// Type alias for a shared, interior-mutable output writer.
+pub type Output = Rc<RefCell<dyn Write>>;
+
+/// The state of an evaluation that is independent of the environment.
+pub struct Context {
+    /// Writer that output builtins write to.
+    output: Output,
+    /// Stack of the source files currently being evaluated, innermost last.
+    files: Vec<PathBuf>,
+    /// Number of evaluation steps taken since the step count was last reset.
+    steps: u64,
+    /// Maximum number of evaluation steps, if evaluation is bounded.
+    step_limit: Option<u64>,
+    /// Whether conditions must be booleans rather than any truthy value.
+    strict_booleans: bool,
+    /// Maximum number of environments enclosing the environment of a scope,
+    /// if scopes are bounded.
+    depth_limit: Option<usize>,
+    /// Whether builtins that access the host system are disabled.
+    sandboxed: bool,
+    /// Arguments passed to the program, e.g., on the command line.
+    args: Vec<String>,
+    /// Generator of the pseudo-random numbers of builtins such as `rand`.
+    random: Random,
+}
+
+impl Context {
+    /// Construct a new context that writes output to the given writer.
+    pub fn new(output: Output) -> Context {
+        Context {
+            output,
+            files: Vec::new(),
+            steps: 0,
+            step_limit: None,
+            strict_booleans: false,
+            depth_limit: None,
+            sandboxed: false,
+            args: Vec::new(),
+            random: Random::default(),
+        }
+    }
+
+    /// Bound evaluation to the given number of steps, i.e., statements and
+    /// expressions evaluated, after which evaluation fails with an
+    /// [`ErrorKind::StepLimit`](super::error::ErrorKind::StepLimit) error.
+    /// This keeps a program that never terminates from running forever.
+    pub fn with_step_limit(mut self, limit: u64) -> Context {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// Bound the depth of nested scopes, i.e., the number of environments
+    /// enclosing that of a function call or block, past which evaluation
+    /// fails with an
+    /// [`ErrorKind::DepthLimit`](super::error::ErrorKind::DepthLimit) error.
+    /// This keeps an environment passed in by an embedder, or a program of
+    /// deeply nested blocks, from building an unbounded chain of scopes.
+    pub fn with_depth_limit(mut self, limit: usize) -> Context {
+        self.depth_limit = Some(limit);
+        self
+    }
+
+    /// Check the depth of a new scope, failing if the depth limit is exceeded.
+    pub fn check_depth(&self, depth: usize) -> Result<(), EvaluationError> {
+        match self.depth_limit {
+            Some(limit) if depth > limit => Err(EvaluationError::depth_limit(format!(
+                "scope depth limit exceeded: limit={}",
+                limit
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Require the condition of an `if` to be a boolean, failing with an
+    /// [`ErrorKind::TypeMismatch`](super::error::ErrorKind::TypeMismatch)
+    /// error otherwise, rather than treating every value other than `false`
+    /// and `null` as true.
+    pub fn with_strict_booleans(mut self) -> Context {
+        self.strict_booleans = true;
+        self
+    }
+
+    /// Returns whether conditions must be booleans.
+    pub fn strict_booleans(&self) -> bool {
+        self.strict_booleans
+    }
+
+    /// Disable the builtins that access the host system, e.g., `import`,
+    /// `read_file`, `write_file`, `getenv`, `now`, `rand`, and `shuffle`, which
+    /// then fail with an
+    /// [`ErrorKind::Sandbox`](super::error::ErrorKind::Sandbox) error. This
+    /// keeps an untrusted program from reading or changing the host's files
+    /// and environment, and its result determined by its source.
+    pub fn with_sandbox(mut self) -> Context {
+        self.sandboxed = true;
+        self
+    }
+
+    /// Check that the given builtin, which accesses the host system, may be
+    /// called, failing if the context is sandboxed.
+    pub fn check_sandbox(&self, builtin: &str) -> Result<(), EvaluationError> {
+        if self.sandboxed {
+            return Err(EvaluationError::sandbox(format!(
+                "`{}` is disabled in the sandbox",
+                builtin
+            )));
+        }
+        Ok(())
+    }
+
+    /// Pass the given arguments to the program, which it can read with the
+    /// `args` builtin.
+    pub fn with_args(mut self, args: Vec<String>) -> Context {
+        self.args = args;
+        self
+    }
+
+    /// Returns the arguments passed to the program.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Seed the pseudo-random numbers of builtins such as `rand` and `shuffle`,
+    /// so the same seed produces the same numbers on every run. Otherwise, the
+    /// numbers are seeded from the current time.
+    pub fn with_seed(mut self, seed: u64) -> Context {
+        self.random = Random::with_seed(seed);
+        self
+    }
+
+    /// Returns the generator of pseudo-random numbers.
+    pub(crate) fn random(&mut self) -> &mut Random {
+        &mut self.random
+    }
+
+    /// Count an evaluation step, failing if the step limit is exceeded.
+    pub fn step(&mut self) -> Result<(), EvaluationError> {
+        self.steps += 1;
+        match self.step_limit {
+            Some(limit) if self.steps > limit => Err(EvaluationError::step_limit(format!(
+                "evaluation step limit exceeded: limit={}",
+                limit
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reset the step count, giving the next evaluation the full step limit.
+    pub fn reset_steps(&mut self) {
+        self.steps = 0;
+    }
+
+    /// Returns the writer that output builtins write to.
+    pub fn output(&self) -> Output {
+        Rc::clone(&self.output)
+    }
+
+    /// Returns the directory that relative import paths are resolved against:
+    /// the directory of the innermost source file being evaluated, or the
+    /// current working directory if no source file is being evaluated.
+    pub fn base_dir(&self) -> PathBuf {
+        self.files
+            .last()
+            .and_then(|file| file.parent())
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
+    }
+
+    /// Returns whether the given source file is currently being evaluated.
+    pub fn is_evaluating(&self, path: &Path) -> bool {
+        self.files.iter().any(|file| file == path)
+    }
+
+    /// Mark the given source file as being evaluated until the matching call
+    /// to [`Context::pop_file`].
+    pub fn push_file(&mut self, path: PathBuf) {
+        self.files.push(path);
+    }
+
+    /// Mark the innermost source file as no longer being evaluated.
+    pub fn pop_file(&mut self) {
+        self.files.pop();
+    }
+}
+
+impl Default for Context {
+    /// Construct a new context that writes output to STDOUT.
+    fn default() -> Self {
+        Context::new(Rc::new(RefCell::new(io::stdout())))
+    }
+}
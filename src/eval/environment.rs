@@ -1,9 +1,9 @@
 //! This module defines a programming environment within Monkey.
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::eval::object::Object;
+use crate::symbol::{Symbol, SymbolMap};
 
 /// Type alias for shared, interior-mutable environment.
 pub type Env = Rc<RefCell<Environment>>;
@@ -11,47 +11,277 @@ pub type Env = Rc<RefCell<Environment>>;
 /// A wrapper around the stored values obtained during evaluation.
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct Environment {
-    store: HashMap<String, Rc<Object>>,
+    store: SymbolMap<Rc<Object>>,
     /// Outer/ enclosing environment that is being extended by the Environment
     /// instance.
     outer: Option<Env>,
+    /// Number of environments enclosing this one.
+    depth: usize,
 }
 
 impl Environment {
     /// Construct a new blank environment.
     pub fn new() -> Environment {
         Environment {
-            store: HashMap::new(),
+            store: SymbolMap::default(),
             outer: None,
+            depth: 0,
         }
     }
 
     /// Constructs a new enclosed environment within the outer environment.
     pub fn new_enclosed_environment(outer: &Env) -> Environment {
+        Environment::with_capacity(outer, 0)
+    }
+
+    /// Constructs a new enclosed environment within the outer environment
+    /// with room for the given number of bindings, e.g., the parameters of a
+    /// function, before reallocating.
+    pub fn with_capacity(outer: &Env, capacity: usize) -> Environment {
         Environment {
-            store: HashMap::new(),
+            store: SymbolMap::with_capacity_and_hasher(capacity, Default::default()),
+            depth: outer.borrow().depth + 1,
             outer: Some(Rc::clone(outer)),
         }
     }
 
+    /// Returns whether the environment is enclosed within an outer one, e.g.,
+    /// the environment of a function call, rather than a global environment.
+    pub fn is_enclosed(&self) -> bool {
+        self.outer.is_some()
+    }
+
+    /// Returns the number of environments enclosing this one, e.g., `0` for a
+    /// global environment.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
     /// Retrieves the value associated with a key, if it exists.
-    pub fn get(&self, name: &str) -> Option<Rc<Object>> {
-        match self.store.get(name) {
-            Some(obj) => Some(Rc::clone(obj)),
-            None => {
-                // Check the enclosing environment as well, if it exists.
-                if let Some(outer) = &self.outer {
-                    outer.borrow().get(name)
-                } else {
-                    None
-                }
+    pub fn get(&self, name: Symbol) -> Option<Rc<Object>> {
+        if let Some(obj) = self.store.get(&name) {
+            return Some(Rc::clone(obj));
+        }
+
+        // Check the enclosing environments as well, innermost first. The chain
+        // is walked in a loop rather than recursively, so that lookups in a
+        // deeply nested environment cannot overflow the stack.
+        let mut outer = self.outer.clone();
+        while let Some(env) = outer {
+            let env = env.borrow();
+            if let Some(obj) = env.store.get(&name) {
+                return Some(Rc::clone(obj));
             }
+            outer = env.outer.clone();
         }
+
+        None
+    }
+
+    /// Returns the bindings of the local scope, excluding any enclosing
+    /// environments, sorted by name.
+    pub fn bindings(&self) -> Vec<(String, Rc<Object>)> {
+        let mut bindings: Vec<(String, Rc<Object>)> = self
+            .store
+            .iter()
+            .map(|(name, val)| (name.to_string(), Rc::clone(val)))
+            .collect();
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
+    /// Returns the bindings visible from this scope, including those of every
+    /// enclosing environment, sorted by name. Bindings of inner scopes shadow
+    /// bindings of the same name in outer scopes.
+    pub fn all_bindings(&self) -> Vec<(String, Rc<Object>)> {
+        let mut bindings: Vec<(String, Rc<Object>)> = self
+            .visible_bindings()
+            .into_iter()
+            .map(|(name, val)| (name.to_string(), val))
+            .collect();
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
+    /// Returns the bindings visible from this scope by symbol.
+    fn visible_bindings(&self) -> SymbolMap<Rc<Object>> {
+        let mut visible = self.store.clone();
+
+        // Add the bindings of the enclosing environments, innermost first, not
+        // shadowed by those already added. The chain is walked in a loop, as in
+        // `get`.
+        let mut outer = self.outer.clone();
+        while let Some(env) = outer {
+            let env = env.borrow();
+            for (name, val) in &env.store {
+                visible.entry(*name).or_insert_with(|| Rc::clone(val));
+            }
+            outer = env.outer.clone();
+        }
+        visible
     }
 
     /// Sets the value for a given key. If the key is already present in the
     /// environment, its value is updated.
-    pub fn set(&mut self, name: &str, val: Rc<Object>) {
-        self.store.insert(name.to_string(), val);
+    pub fn set(&mut self, name: Symbol, val: Rc<Object>) {
+        self.store.insert(name, val);
+    }
+
+    /// Updates the value for a given key in the innermost environment in which
+    /// it is bound, returning whether the key was bound. Closures sharing that
+    /// environment observe the new value.
+    pub fn assign(&mut self, name: Symbol, val: Rc<Object>) -> bool {
+        if let Some(existing) = self.store.get_mut(&name) {
+            *existing = val;
+            return true;
+        }
+
+        // Walk the enclosing environments in a loop, as in `get`
+        let mut outer = self.outer.clone();
+        while let Some(env) = outer {
+            let mut env = env.borrow_mut();
+            if let Some(existing) = env.store.get_mut(&name) {
+                *existing = val;
+                return true;
+            }
+            outer = env.outer.clone();
+        }
+
+        false
+    }
+
+    /// Removes the binding for a given key, returning its previous value, if
+    /// it was present. Only the current scope is affected; bindings of the
+    /// same name in enclosing environments are left intact and become visible
+    /// again.
+    pub fn remove(&mut self, name: Symbol) -> Option<Rc<Object>> {
+        self.store.remove(&name)
+    }
+}
+
+/// Unlinks the chain of enclosing environments that are only referenced by
+/// this one in a loop, as dropping them recursively could overflow the stack
+/// for a deeply nested environment.
+impl Drop for Environment {
+    fn drop(&mut self) {
+        let mut outer = self.outer.take();
+        while let Some(env) = outer {
+            outer = match Rc::try_unwrap(env) {
+                Ok(env) => env.into_inner().outer.take(),
+                // The rest of the chain is still referenced elsewhere
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render bindings as `name = value` strings for comparison.
+    fn render(bindings: Vec<(String, Rc<Object>)>) -> Vec<String> {
+        bindings
+            .into_iter()
+            .map(|(name, val)| format!("{} = {}", name, val))
+            .collect()
+    }
+
+    #[test]
+    fn test_bindings() {
+        let outer: Env = Rc::new(RefCell::new(Environment::new()));
+        outer
+            .borrow_mut()
+            .set("x".into(), Rc::new(Object::Integer(1)));
+        outer
+            .borrow_mut()
+            .set("y".into(), Rc::new(Object::Integer(2)));
+
+        let mut inner = Environment::new_enclosed_environment(&outer);
+        inner.set("y".into(), Rc::new(Object::Integer(3)));
+        inner.set("z".into(), Rc::new(Object::Integer(4)));
+
+        assert_eq!(render(outer.borrow().bindings()), ["x = 1", "y = 2"]);
+        assert_eq!(render(inner.bindings()), ["y = 3", "z = 4"]);
+        assert_eq!(render(inner.all_bindings()), ["x = 1", "y = 3", "z = 4"]);
+    }
+
+    #[test]
+    fn test_get_deep_chain() {
+        let global: Env = Rc::new(RefCell::new(Environment::new()));
+        global
+            .borrow_mut()
+            .set("x".into(), Rc::new(Object::Integer(1)));
+
+        // Deep enough to overflow the stack if lookups, assignments, listings,
+        // or drops recursed
+        let mut env = Rc::clone(&global);
+        for _ in 0..200_000 {
+            env = Rc::new(RefCell::new(Environment::new_enclosed_environment(&env)));
+        }
+        assert_eq!(env.borrow().depth(), 200_000);
+        assert_eq!(
+            env.borrow().get("x".into()),
+            Some(Rc::new(Object::Integer(1)))
+        );
+        assert_eq!(env.borrow().get("y".into()), None);
+
+        assert!(env
+            .borrow_mut()
+            .assign("x".into(), Rc::new(Object::Integer(2))));
+        assert!(!env
+            .borrow_mut()
+            .assign("y".into(), Rc::new(Object::Integer(2))));
+        assert_eq!(
+            global.borrow().get("x".into()),
+            Some(Rc::new(Object::Integer(2)))
+        );
+
+        env.borrow_mut()
+            .set("z".into(), Rc::new(Object::Integer(3)));
+        assert_eq!(
+            env.borrow().all_bindings(),
+            [
+                ("x".to_string(), Rc::new(Object::Integer(2))),
+                ("z".to_string(), Rc::new(Object::Integer(3))),
+            ]
+        );
+
+        // Dropping the chain leaves environments referenced elsewhere intact
+        drop(env);
+        assert_eq!(
+            global.borrow().get("x".into()),
+            Some(Rc::new(Object::Integer(2)))
+        );
+    }
+
+    #[test]
+    fn test_depth() {
+        let outer: Env = Rc::new(RefCell::new(Environment::new()));
+        let inner: Env = Rc::new(RefCell::new(Environment::new_enclosed_environment(&outer)));
+        let innermost = Environment::with_capacity(&inner, 1);
+
+        assert_eq!(outer.borrow().depth(), 0);
+        assert_eq!(inner.borrow().depth(), 1);
+        assert_eq!(innermost.depth(), 2);
+    }
+
+    #[test]
+    fn test_remove_exposes_outer_binding() {
+        let outer: Env = Rc::new(RefCell::new(Environment::new()));
+        outer
+            .borrow_mut()
+            .set("x".into(), Rc::new(Object::Integer(1)));
+
+        let mut inner = Environment::new_enclosed_environment(&outer);
+        inner.set("x".into(), Rc::new(Object::Integer(2)));
+        assert_eq!(inner.get("x".into()), Some(Rc::new(Object::Integer(2))));
+
+        assert_eq!(inner.remove("x".into()), Some(Rc::new(Object::Integer(2))));
+        assert_eq!(inner.get("x".into()), Some(Rc::new(Object::Integer(1))));
+
+        // The outer binding is not removed through the inner scope
+        assert_eq!(inner.remove("x".into()), None);
+        assert_eq!(inner.get("x".into()), Some(Rc::new(Object::Integer(1))));
     }
 }
@@ -6,21 +6,190 @@ during evaluation.
 */
 use std::fmt;
 
+use crate::token::Position;
+
+/// The category of an error encountered while performing evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// An operator was applied to operands it does not support.
+    UnknownOperator,
+    /// A value of an unexpected type was encountered.
+    TypeMismatch,
+    /// A value of the expected type was outside the range a builtin accepts.
+    InvalidArgument,
+    /// An identifier was not bound in the environment.
+    IdentifierNotFound,
+    /// A call was made on a value that is not a function.
+    NotAFunction,
+    /// A function was called with the wrong number of arguments.
+    WrongArgCount,
+    /// A keyword argument did not name exactly one unbound parameter.
+    InvalidKeyword,
+    /// An integer was divided by zero.
+    DivisionByZero,
+    /// An integer operation overflowed.
+    Overflow,
+    /// An integer was raised to a negative power.
+    NegativeExponent,
+    /// A value was indexed with an unsupported index.
+    IndexError,
+    /// A hash literal contained the same key more than once.
+    DuplicateKey,
+    /// An I/O operation, e.g., writing output, failed.
+    Io,
+    /// Another source file could not be imported.
+    Import,
+    /// Evaluation took more steps than the context's step limit allows.
+    StepLimit,
+    /// A `break` or `continue` was used outside of a loop.
+    ControlFlow,
+    /// Scopes were nested more deeply than the context's scope depth limit
+    /// allows.
+    DepthLimit,
+    /// A builtin that accesses the host system was called while the context
+    /// is sandboxed.
+    Sandbox,
+}
+
 /// An error encountered while performing evaluation.
 #[derive(Debug, Clone)]
-pub struct EvaluationError(String);
+pub struct EvaluationError {
+    /// The category of the error.
+    kind: ErrorKind,
+    /// The human-readable message to display.
+    msg: String,
+    /// The position in the source code of the expression that raised the
+    /// error, if known.
+    position: Option<Position>,
+}
 
+/// Displays only the message. The position, if any, is displayed by the
+/// crate-level [`Error`](crate::Error).
 impl fmt::Display for EvaluationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.msg)
     }
 }
 
 impl std::error::Error for EvaluationError {}
 
 impl EvaluationError {
-    /// Construct a new parser error with the given message to display.
-    pub fn new(msg: String) -> Self {
-        EvaluationError(msg)
+    /// Construct a new evaluation error of the given kind with the given
+    /// message to display.
+    pub fn new(kind: ErrorKind, msg: String) -> Self {
+        EvaluationError {
+            kind,
+            msg,
+            position: None,
+        }
+    }
+
+    /// Returns the category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the position in the source code of the expression that raised
+    /// the error, if known.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Attach the position of the expression the error was raised from,
+    /// unless the error already carries the position of a more deeply nested
+    /// expression.
+    pub fn at(mut self, position: Position) -> Self {
+        self.position.get_or_insert(position);
+        self
+    }
+
+    /// Construct a new [`ErrorKind::UnknownOperator`] error.
+    pub fn unknown_operator(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::UnknownOperator, msg)
+    }
+
+    /// Construct a new [`ErrorKind::TypeMismatch`] error.
+    pub fn type_mismatch(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::TypeMismatch, msg)
+    }
+
+    /// Construct a new [`ErrorKind::InvalidArgument`] error.
+    pub fn invalid_argument(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::InvalidArgument, msg)
+    }
+
+    /// Construct a new [`ErrorKind::IdentifierNotFound`] error.
+    pub fn identifier_not_found(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::IdentifierNotFound, msg)
+    }
+
+    /// Construct a new [`ErrorKind::NotAFunction`] error.
+    pub fn not_a_function(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::NotAFunction, msg)
+    }
+
+    /// Construct a new [`ErrorKind::WrongArgCount`] error.
+    pub fn wrong_arg_count(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::WrongArgCount, msg)
+    }
+
+    /// Construct a new [`ErrorKind::InvalidKeyword`] error.
+    pub fn invalid_keyword(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::InvalidKeyword, msg)
+    }
+
+    /// Construct a new [`ErrorKind::DivisionByZero`] error.
+    pub fn division_by_zero(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::DivisionByZero, msg)
+    }
+
+    /// Construct a new [`ErrorKind::Overflow`] error.
+    pub fn overflow(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::Overflow, msg)
+    }
+
+    /// Construct a new [`ErrorKind::NegativeExponent`] error.
+    pub fn negative_exponent(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::NegativeExponent, msg)
+    }
+
+    /// Construct a new [`ErrorKind::IndexError`] error.
+    pub fn index_error(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::IndexError, msg)
+    }
+
+    /// Construct a new [`ErrorKind::DuplicateKey`] error.
+    pub fn duplicate_key(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::DuplicateKey, msg)
+    }
+
+    /// Construct a new [`ErrorKind::Io`] error.
+    pub fn io(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::Io, msg)
+    }
+
+    /// Construct a new [`ErrorKind::Import`] error.
+    pub fn import(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::Import, msg)
+    }
+
+    /// Construct a new [`ErrorKind::StepLimit`] error.
+    pub fn step_limit(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::StepLimit, msg)
+    }
+
+    /// Construct a new [`ErrorKind::ControlFlow`] error.
+    pub fn control_flow(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::ControlFlow, msg)
+    }
+
+    /// Construct a new [`ErrorKind::DepthLimit`] error.
+    pub fn depth_limit(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::DepthLimit, msg)
+    }
+
+    /// Construct a new [`ErrorKind::Sandbox`] error.
+    pub fn sandbox(msg: String) -> Self {
+        EvaluationError::new(ErrorKind::Sandbox, msg)
     }
 }
@@ -10,6 +10,7 @@ use std::hash::Hash;
 use std::rc::Rc;
 
 use crate::eval::{environment, Builtin};
+use crate::lexer;
 use crate::parser::ast;
 
 /// Represents objects in Monkey that can represent the values the source AST
@@ -26,9 +27,14 @@ pub enum Object {
     Null,
     /// Represents a return value object
     ReturnValue(Rc<Object>),
+    /// Signals a `break` out of the innermost enclosing loop.
+    Break,
+    /// Signals a `continue` to the next iteration of the innermost enclosing
+    /// loop.
+    Continue,
     /// Represents a function literal with given parameters, a body block
     /// statement, and its environment/context.
-    Function(Vec<String>, ast::BlockStatement, environment::Env),
+    Function(Vec<ast::Parameter>, ast::BlockStatement, environment::Env),
     /// A built-in function
     Builtin(Builtin),
     /// An array, an ordered list of elements of possibly different types.
@@ -56,7 +62,102 @@ impl Display for HashableObject {
     }
 }
 
+/// Converts a hash key back into the object it was made from, the inverse of
+/// [`Object::as_hashable`].
+impl From<&HashableObject> for Object {
+    fn from(key: &HashableObject) -> Self {
+        match key {
+            HashableObject::Integer(int) => Object::Integer(*int),
+            HashableObject::Boolean(bool) => Object::Boolean(*bool),
+            HashableObject::String(str) => Object::String(str.clone()),
+        }
+    }
+}
+
 impl Object {
+    /// Render the object like its `Display` form, except that a string is
+    /// rendered as a quoted string literal with its special characters
+    /// escaped, e.g., `"a\nb\"c"`, which lexes back to the same string.
+    pub fn escaped(&self) -> String {
+        match self {
+            Object::String(str) => lexer::escape(str),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Render the object as Monkey source code that evaluates to an equal
+    /// value, if possible. Unlike the `Display` form, a function is rendered
+    /// as a function literal that parses back to the same AST. Builtins, and
+    /// functions that close over the bindings of an enclosed scope, e.g., a
+    /// function returned by another function, cannot be rendered, as the
+    /// bindings they capture cannot be.
+    pub fn to_source(&self) -> Option<String> {
+        match self {
+            Object::Integer(_) | Object::Boolean(_) | Object::Null => Some(self.to_string()),
+            Object::String(_) => Some(self.escaped()),
+            Object::Function(params, body, env) if !env.borrow().is_enclosed() => {
+                Some(ast::Expression::Fn(params.clone(), body.clone()).pretty(0))
+            }
+            Object::Array(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| element.to_source())
+                    .collect::<Option<Vec<String>>>()?;
+                Some(format!("[{}]", elements.join(", ")))
+            }
+            Object::Hash(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(k, v)| {
+                        Some(format!(
+                            "{}: {}",
+                            Object::from(&**k).escaped(),
+                            v.to_source()?
+                        ))
+                    })
+                    .collect::<Option<Vec<String>>>()?;
+                Some(format!("{{{}}}", entries.join(", ")))
+            }
+            _ => None,
+        }
+    }
+
+    /// Return a deep copy of the object, where arrays and hashes, including any
+    /// nested within them, are copied into fresh allocations rather than
+    /// shared. Other objects are returned as is.
+    pub fn deep_copy(object: &Rc<Object>) -> Rc<Object> {
+        match &**object {
+            Object::Array(elements) => Rc::new(Object::Array(
+                elements.iter().map(Object::deep_copy).collect(),
+            )),
+            Object::Hash(entries) => Rc::new(Object::Hash(
+                entries
+                    .iter()
+                    .map(|(k, v)| (Rc::clone(k), Object::deep_copy(v)))
+                    .collect(),
+            )),
+            _ => Rc::clone(object),
+        }
+    }
+
+    /// Return the name of the object's type, as error messages refer to it,
+    /// e.g., `INTEGER`. A return value has the type of the value it returns.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => "INTEGER",
+            Object::Boolean(_) => "BOOLEAN",
+            Object::String(_) => "STRING",
+            Object::Null => "NULL",
+            Object::ReturnValue(object) => object.type_name(),
+            Object::Break => "BREAK",
+            Object::Continue => "CONTINUE",
+            Object::Function(..) => "FUNCTION",
+            Object::Builtin(_) => "BUILTIN",
+            Object::Array(_) => "ARRAY",
+            Object::Hash(_) => "HASH",
+        }
+    }
+
     /// Return the object as a [`HashableObject`], if possible.
     pub fn as_hashable(&self) -> Option<HashableObject> {
         match self {
@@ -75,8 +176,10 @@ impl Display for Object {
             Object::Boolean(bool) => write!(f, "{}", bool),
             Object::Null => write!(f, "null"),
             Object::ReturnValue(object) => write!(f, "{}", object),
+            Object::Break => write!(f, "break"),
+            Object::Continue => write!(f, "continue"),
             Object::Function(params, body, _env) => {
-                let params = params.join(", ");
+                let params = ast::display_parameters(params);
                 write!(
                     f,
                     "fn({}) {{\n {} \n}}",
@@ -0,0 +1,96 @@
+/*!
+# Random
+
+Defines `Random`, the pseudo-random number generator behind builtins such as
+`rand`. It implements SplitMix64, which is fast, passes common statistical
+tests, and is fully determined by its seed, so an evaluation seeded with a
+fixed value is reproducible. It is not suitable for cryptographic use.
+*/
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A seedable pseudo-random number generator.
+#[derive(Debug, Clone)]
+pub(crate) struct Random {
+    /// The state, advanced by a fixed increment on every draw.
+    state: u64,
+}
+
+impl Random {
+    /// The increment of the state, the golden ratio in fixed point.
+    const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    /// Construct a new generator that produces the sequence for the given
+    /// seed.
+    pub(crate) fn with_seed(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    /// Returns the next number of the sequence.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(Self::GAMMA);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns the next number of the sequence reduced to the range
+    /// `[0, bound)`, which must not be empty.
+    pub(crate) fn below(&mut self, bound: u64) -> u64 {
+        // Reject the draws past the largest multiple of the bound, which would
+        // otherwise make the smallest numbers more likely
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let n = self.next_u64();
+            if n < zone {
+                return n % bound;
+            }
+        }
+    }
+}
+
+impl Default for Random {
+    /// Construct a new generator seeded from the current time, so each
+    /// evaluation produces a different sequence.
+    fn default() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Random::with_seed(seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_sequence() {
+        // The sequence is determined by the seed alone
+        let mut random = Random::with_seed(1234567);
+        let mut again = Random::with_seed(1234567);
+        let sequence: Vec<u64> = (0..5).map(|_| random.next_u64()).collect();
+        assert_eq!(
+            sequence,
+            (0..5).map(|_| again.next_u64()).collect::<Vec<_>>()
+        );
+        assert_ne!(sequence[0], Random::with_seed(7654321).next_u64());
+
+        // The first output for a zero seed, as given by the reference
+        // implementation
+        assert_eq!(Random::with_seed(0).next_u64(), 0xe220_a839_7b1d_cdaf);
+    }
+
+    #[test]
+    fn test_below() {
+        let mut random = Random::with_seed(42);
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let n = random.below(6);
+            assert!(n < 6);
+            seen[n as usize] = true;
+        }
+        assert_eq!(seen, [true; 6]);
+        assert_eq!(random.below(1), 0);
+    }
+}
@@ -0,0 +1,70 @@
+/*!
+# Interpreter
+
+Defines the `Interpreter`, the main entry point for embedding Monkey. An
+interpreter wraps the parser and evaluator, persisting its environment across
+evaluations.
+*/
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::error::Error;
+use crate::eval::{self, context::Context, environment::Env, object::Object};
+use crate::parser;
+
+/// A Monkey interpreter whose environment persists across evaluations.
+pub struct Interpreter {
+    /// The environment shared by every evaluation.
+    env: Env,
+    /// The evaluation context, e.g., where builtin output is written.
+    ctx: Context,
+}
+
+impl Interpreter {
+    /// Construct a new interpreter with a blank environment that writes output
+    /// to STDOUT.
+    pub fn new() -> Interpreter {
+        Interpreter::with_context(Context::default())
+    }
+
+    /// Construct a new interpreter with a blank environment that evaluates
+    /// within the given context.
+    pub fn with_context(ctx: Context) -> Interpreter {
+        Interpreter {
+            env: Rc::new(RefCell::new(Default::default())),
+            ctx,
+        }
+    }
+
+    /// Parse and evaluate the given source code, returning the value of the
+    /// last evaluated statement. Bindings are kept for subsequent calls. If
+    /// the context has a step limit, it bounds each call separately.
+    pub fn eval_str(&mut self, src: &str) -> Result<Rc<Object>, Error> {
+        let program = parser::parse(src)?;
+        self.ctx.reset_steps();
+        Ok(eval::eval_with_context(program, &self.env, &mut self.ctx)?)
+    }
+
+    /// Parse and evaluate source code read from the file at the given path,
+    /// returning the value of the last evaluated statement. Relative `import`
+    /// paths within the source are resolved against the file's directory.
+    pub fn eval_source(&mut self, src: &str, path: &Path) -> Result<Rc<Object>, Error> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.ctx.push_file(path);
+        let result = self.eval_str(src);
+        self.ctx.pop_file();
+        result
+    }
+
+    /// Discard all bindings, resetting the interpreter to a blank environment.
+    pub fn reset(&mut self) {
+        self.env = Rc::new(RefCell::new(Default::default()));
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
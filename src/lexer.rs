@@ -5,4 +5,4 @@ Defines the methods required to convert a stream of source code into a
 token stream.
 */
 pub mod parse;
-pub use parse::Lexer;
+pub use parse::{escape, tokenize, Lexer};
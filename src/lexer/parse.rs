@@ -6,6 +6,25 @@ of `Token`s, handling identifiers, numbers, operators, and whitespace.
 use crate::token;
 
 /// The lexer to convert source code into tokens representing the source code.
+///
+/// Tokens can be read one at a time with [`Lexer::next_token`], or by
+/// iterating over the lexer, which yields every token up to and including the
+/// terminating `Token::Eof` before finishing.
+///
+/// # Examples
+///
+/// ```
+/// use monkey_rs::lexer::Lexer;
+/// use monkey_rs::token::Token;
+///
+/// let mut lexer = Lexer::new("let x = 5;");
+/// assert_eq!(lexer.next_token(), Token::Let);
+/// assert_eq!(lexer.next_token(), Token::Ident("x".to_string()));
+/// assert_eq!(lexer.next_token(), Token::Assign);
+/// assert_eq!(lexer.next_token(), Token::Int(5));
+/// assert_eq!(lexer.next_token(), Token::Semicolon);
+/// assert_eq!(lexer.next_token(), Token::Eof);
+/// ```
 #[derive(Debug)]
 pub struct Lexer<'a> {
     /// the input source code to tokenize
@@ -16,6 +35,14 @@ pub struct Lexer<'a> {
     read_position: usize,
     /// current char under examination
     ch: Option<char>,
+    /// position of the current char in the input
+    ch_position: token::Position,
+    /// position of the first char of the last token read
+    token_position: token::Position,
+    /// whether a newline precedes the last token read
+    newline_before: bool,
+    /// whether the iterator has yielded the terminating `Token::Eof`
+    finished: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -29,6 +56,10 @@ impl<'a> Lexer<'a> {
             position: 0,
             read_position: 0,
             ch: None,
+            ch_position: token::Position::default(),
+            token_position: token::Position::default(),
+            newline_before: false,
+            finished: false,
         };
         // put the lexer in an initial working state referencing the first
         // character
@@ -39,39 +70,44 @@ impl<'a> Lexer<'a> {
     /// Update the lexer state to reflect the next character in the input, if
     /// any, and advance the position in the input.
     fn read_char(&mut self) {
-        // check if we have reached end of the input
-        if self.read_position >= self.input.len() {
-            self.ch = None
-        } else {
-            let remainder = &self.input[self.read_position..];
-            if let Some((_, c)) = remainder.char_indices().next() {
-                self.ch = Some(c);
-                self.position = self.read_position;
-                // advance the read position to be a character ahead of the
-                // current character position
-                self.read_position += c.len_utf8();
-                return;
+        // move the position past the current character
+        match self.ch {
+            Some('\n') => {
+                self.ch_position.line += 1;
+                self.ch_position.column = 1;
             }
+            Some(_) => self.ch_position.column += 1,
+            None => {}
         }
-        // reached EOF
-        self.ch = None;
+
+        // The read position always lies on a character boundary, as it only
+        // ever advances by the UTF-8 length of a whole character, so every
+        // slice of the input taken between `position`s is valid.
+        self.ch = self.peek_char();
         self.position = self.read_position;
+        if let Some(c) = self.ch {
+            // advance the read position to be a character ahead of the
+            // current character position
+            self.read_position += c.len_utf8();
+        }
     }
 
     /// Determine and return the next token in the input from the current
     /// character position.
     pub fn next_token(&mut self) -> token::Token {
         // consume character(s) until no whitespace
-        while matches!(self.ch, Some(c) if c.is_whitespace()) {
+        self.newline_before = false;
+        while let Some(c) = self.ch.filter(|c| c.is_whitespace()) {
+            self.newline_before |= c == '\n';
             self.read_char();
         }
+        self.token_position = self.ch_position;
 
         let token = match self.ch {
             // Single character tokens
             Some('+') => token::Token::Plus,
             Some('-') => token::Token::Minus,
             Some('/') => token::Token::Slash,
-            Some('*') => token::Token::Asterisk,
             Some('<') => token::Token::Lt,
             Some('>') => token::Token::Gt,
             Some(';') => token::Token::Semicolon,
@@ -83,19 +119,42 @@ impl<'a> Lexer<'a> {
             Some('[') => token::Token::LBracket,
             Some(']') => token::Token::RBracket,
             Some(':') => token::Token::Colon,
+            // An underscore followed by an identifier character starts an
+            // identifier, e.g., `_x`, rather than being a wildcard
+            Some('_') if self.peek_char().is_some_and(is_identifier_char) => {
+                let ident = self.read_indentifier();
+                return token::lookup_ident(&ident);
+            }
+            Some('_') => token::Token::Underscore,
+            Some('.') if self.input[self.position..].starts_with("...") => {
+                self.read_char();
+                self.read_char();
+                self.read_char();
+                return token::Token::Ellipsis;
+            }
             Some('"') => {
-                let str = self.read_string();
-                return token::Token::String(str);
+                return match self.read_string() {
+                    Some(str) => token::Token::String(str),
+                    // String is missing its closing quotation
+                    None => token::Token::UnterminatedString,
+                };
+            }
+
+            Some('r') if self.peek_char() == Some('"') => {
+                return match self.read_raw_string() {
+                    Some(str) => token::Token::String(str),
+                    // Raw string is missing its closing quotation
+                    None => token::Token::UnterminatedString,
+                };
             }
 
             // Multi-character tokens (e.g., identifier, integer, etc.)
-            Some(c) if c.is_ascii_alphabetic() => {
+            Some(c) if c.is_alphabetic() => {
                 let ident = self.read_indentifier();
                 return token::lookup_ident(&ident);
             }
             Some(c) if c.is_ascii_digit() => {
-                let literal = self.read_number();
-                return token::Token::Int(literal);
+                return self.read_number();
             }
             Some('=') => {
                 if self.peek_char() == Some('=') {
@@ -103,9 +162,23 @@ impl<'a> Lexer<'a> {
                     self.read_char();
                     return token::Token::Eq;
                 }
+                if self.peek_char() == Some('>') {
+                    self.read_char();
+                    self.read_char();
+                    return token::Token::FatArrow;
+                }
                 self.read_char();
                 return token::Token::Assign;
             }
+            Some('*') => {
+                if self.peek_char() == Some('*') {
+                    self.read_char();
+                    self.read_char();
+                    return token::Token::Power;
+                }
+                self.read_char();
+                return token::Token::Asterisk;
+            }
             Some('!') => {
                 if self.peek_char() == Some('=') {
                     self.read_char();
@@ -129,48 +202,106 @@ impl<'a> Lexer<'a> {
         token
     }
 
+    /// Returns the position of the first character of the token last returned
+    /// by [`Lexer::next_token`]. The position of `Token::Eof` is just past the
+    /// end of the input.
+    pub fn token_position(&self) -> token::Position {
+        self.token_position
+    }
+
+    /// Returns whether the whitespace skipped before the token last returned
+    /// by [`Lexer::next_token`] contains a newline, i.e., whether the token
+    /// starts a new line.
+    pub fn newline_before(&self) -> bool {
+        self.newline_before
+    }
+
     /// Reads in an identifier and advances the lexer's position until it
-    /// encounters a non-letter character
+    /// encounters a non-identifier character
     fn read_indentifier(&mut self) -> String {
         let start = self.position;
-        // identifiers can be alphanumeric separated by underscores
-        while matches!(self.ch, Some(c) if c.is_ascii_alphanumeric() || c == '_') {
+        while self.ch.is_some_and(is_identifier_char) {
             self.read_char();
         }
         self.input[start..self.position].to_string()
     }
 
     /// Reads in a number and advances the lexer's position until it encounters
-    /// a non-numeric character. Only supports integer values.
-    fn read_number(&mut self) -> i32 {
+    /// a non-numeric character. Only supports integer values, returning an
+    /// [`IntTooLarge`](token::Token::IntTooLarge) token if the value does not
+    /// fit in a 64-bit signed integer.
+    fn read_number(&mut self) -> token::Token {
         let start = self.position;
         while matches!(self.ch, Some(c) if c.is_ascii_digit()) {
             self.read_char();
         }
-        self.input[start..self.position]
-            .parse()
-            .expect("Invalid number encountered")
+        let literal = &self.input[start..self.position];
+        match literal.parse() {
+            Ok(int) => token::Token::Int(int),
+            Err(_) => token::Token::IntTooLarge(literal.to_string()),
+        }
     }
 
-    /// Read a string value from the opening quotation character.
-    fn read_string(&mut self) -> String {
+    /// Read a string value from the opening quotation character, replacing
+    /// the escape sequences `\n`, `\t`, `\r`, `\"`, and `\\` with the
+    /// characters they represent. Any other backslash is kept as is. Returns
+    /// `None` if the input ends before the closing quotation.
+    fn read_string(&mut self) -> Option<String> {
         // Skip opening quotation
         self.read_char();
-        let position = self.position;
+        let mut str = String::new();
 
         while let Some(ch) = self.ch {
-            if ch == '"' {
-                break;
+            match ch {
+                '"' => break,
+                '\\' => {
+                    let escaped = match self.peek_char() {
+                        Some('n') => Some('\n'),
+                        Some('t') => Some('\t'),
+                        Some('r') => Some('\r'),
+                        Some('"') => Some('"'),
+                        Some('\\') => Some('\\'),
+                        _ => None,
+                    };
+                    match escaped {
+                        Some(escaped) => {
+                            str.push(escaped);
+                            self.read_char();
+                        }
+                        None => str.push(ch),
+                    }
+                }
+                _ => str.push(ch),
             }
             self.read_char();
         }
+        self.ch?;
 
-        let str = self.input[position..self.position].to_string();
+        // Move past closing quotation
+        self.read_char();
+
+        Some(str)
+    }
+
+    /// Read a raw string value from the leading `r`, keeping every character
+    /// up to the closing quotation as is, including backslashes. Returns `None`
+    /// if the input ends before the closing quotation.
+    fn read_raw_string(&mut self) -> Option<String> {
+        // Skip `r` and opening quotation
+        self.read_char();
+        self.read_char();
+        let start = self.position;
+
+        while matches!(self.ch, Some(c) if c != '"') {
+            self.read_char();
+        }
+        self.ch?;
+        let str = self.input[start..self.position].to_string();
 
         // Move past closing quotation
         self.read_char();
 
-        str
+        Some(str)
     }
 
     /// Peeks the next character from the current position of the lexer.
@@ -179,6 +310,53 @@ impl<'a> Lexer<'a> {
     }
 }
 
+impl Iterator for Lexer<'_> {
+    type Item = token::Token;
+
+    /// Returns the next token in the input. The terminating `Token::Eof` is
+    /// yielded once, after which the iterator is finished.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let token = self.next_token();
+        self.finished = token == token::Token::Eof;
+        Some(token)
+    }
+}
+
+/// Returns whether the character can continue an identifier: a Unicode letter
+/// or digit, or an underscore.
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Tokenize the given input, returning every token in the input up to and
+/// including the terminating `Token::Eof`.
+pub fn tokenize(input: &str) -> Vec<token::Token> {
+    Lexer::new(input).collect()
+}
+
+/// Render the string as a quoted Monkey string literal, escaping the
+/// characters that [`Lexer`] reads as escape sequences, so that lexing the
+/// result produces the original string.
+pub fn escape(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len() + 2);
+    escaped.push('"');
+    for ch in str.chars() {
+        match ch {
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,6 +399,309 @@ mod tests {
         verify_expected_next_token(&expected, &mut l);
     }
 
+    #[test]
+    fn test_unicode_identifiers() {
+        let ident = |name: &str| token::Token::Ident(name.to_string());
+        assert_eq!(
+            vec![
+                ident("café"),
+                token::Token::Assign,
+                ident("λ1"),
+                token::Token::Plus,
+                ident("_x"),
+                token::Token::Semicolon,
+                ident("naïve_名前"),
+                token::Token::Eof,
+            ],
+            tokenize("café = λ1 + _x; naïve_名前")
+        );
+
+        // A digit cannot start an identifier, and a lone underscore is still
+        // the wildcard
+        assert_eq!(
+            vec![
+                token::Token::Int(1),
+                ident("été"),
+                token::Token::Underscore,
+                token::Token::FatArrow,
+                token::Token::Eof,
+            ],
+            tokenize("1été _ =>")
+        );
+    }
+
+    #[test]
+    fn test_lexer_iterator() {
+        let tokens: Vec<token::Token> = Lexer::new("1 + 2").collect();
+        assert_eq!(
+            vec![
+                token::Token::Int(1),
+                token::Token::Plus,
+                token::Token::Int(2),
+                token::Token::Eof,
+            ],
+            tokens
+        );
+
+        // The iterator is finished once `Eof` has been yielded
+        let mut lexer = Lexer::new("");
+        assert_eq!(Some(token::Token::Eof), lexer.next());
+        assert_eq!(None, lexer.next());
+    }
+
+    #[test]
+    fn test_token_positions() {
+        let mut lexer = Lexer::new("let x = 5;\n  \"é\" +\n\n(ab)");
+        let mut positions = Vec::new();
+        loop {
+            let token = lexer.next_token();
+            positions.push((token.clone(), lexer.token_position().to_string()));
+            if token == token::Token::Eof {
+                break;
+            }
+        }
+        let expected = [
+            (token::Token::Let, "1:1"),
+            (token::Token::Ident("x".to_string()), "1:5"),
+            (token::Token::Assign, "1:7"),
+            (token::Token::Int(5), "1:9"),
+            (token::Token::Semicolon, "1:10"),
+            (token::Token::String("é".to_string()), "2:3"),
+            (token::Token::Plus, "2:7"),
+            (token::Token::LParen, "4:1"),
+            (token::Token::Ident("ab".to_string()), "4:2"),
+            (token::Token::RParen, "4:4"),
+            (token::Token::Eof, "4:5"),
+        ];
+        let expected = expected
+            .iter()
+            .map(|(token, position)| (token.clone(), position.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, positions);
+    }
+
+    #[test]
+    fn test_multibyte_positions() {
+        let mut lexer = Lexer::new("let s = \"héllo 🐒\"; r\"🐒\\é\" +\n  héllo🐒 \"\\🐒\"");
+        let mut positions = Vec::new();
+        loop {
+            let token = lexer.next_token();
+            positions.push((token.clone(), lexer.token_position().to_string()));
+            if token == token::Token::Eof {
+                break;
+            }
+        }
+        // Columns count characters rather than bytes, and a character that
+        // cannot start a token is a single illegal token
+        let expected = [
+            (token::Token::Let, "1:1"),
+            (token::Token::Ident("s".to_string()), "1:5"),
+            (token::Token::Assign, "1:7"),
+            (token::Token::String("héllo 🐒".to_string()), "1:9"),
+            (token::Token::Semicolon, "1:18"),
+            (token::Token::String("🐒\\é".to_string()), "1:20"),
+            (token::Token::Plus, "1:27"),
+            (token::Token::Ident("héllo".to_string()), "2:3"),
+            (token::Token::Illegal, "2:8"),
+            (token::Token::String("\\🐒".to_string()), "2:10"),
+            (token::Token::Eof, "2:14"),
+        ];
+        let expected = expected
+            .iter()
+            .map(|(token, position)| (token.clone(), position.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, positions);
+    }
+
+    #[test]
+    fn test_multibyte_boundaries() {
+        // Lexing must never slice the input within a character, wherever the
+        // input ends
+        let input = "let héllo = \"héllo 🐒\" + r\"🐒\" ... héllo🐒";
+        for (end, _) in input.char_indices() {
+            let tokens = tokenize(&input[..end]);
+            assert_eq!(Some(&token::Token::Eof), tokens.last());
+        }
+        assert_eq!(
+            vec![
+                token::Token::Ident("héllo".to_string()),
+                token::Token::String("héllo 🐒".to_string()),
+                token::Token::Eof,
+            ],
+            tokenize("héllo\"héllo 🐒\"")
+        );
+    }
+
+    #[test]
+    fn test_newline_before() {
+        let mut lexer = Lexer::new("a b\n  c\r\n\td \"x\ny\" e");
+        let mut newlines = Vec::new();
+        loop {
+            let token = lexer.next_token();
+            newlines.push((token.clone(), lexer.newline_before()));
+            if token == token::Token::Eof {
+                break;
+            }
+        }
+        assert_eq!(
+            vec![
+                (token::Token::Ident("a".to_string()), false),
+                (token::Token::Ident("b".to_string()), false),
+                (token::Token::Ident("c".to_string()), true),
+                (token::Token::Ident("d".to_string()), true),
+                // Newlines within a token do not count
+                (token::Token::String("x\ny".to_string()), false),
+                (token::Token::Ident("e".to_string()), false),
+                (token::Token::Eof, false),
+            ],
+            newlines
+        );
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(
+            vec![
+                token::Token::String("a\nb\t\"c\"\\".to_string()),
+                token::Token::String("\\d".to_string()),
+                token::Token::Eof,
+            ],
+            tokenize(r#""a\nb\t\"c\"\\" "\d""#)
+        );
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        assert_eq!(
+            vec![
+                token::Token::String("a\\nb\\".to_string()),
+                token::Token::String("a\nb".to_string()),
+                token::Token::Ident("r".to_string()),
+                token::Token::String(String::new()),
+                token::Token::String(String::new()),
+                token::Token::Eof,
+            ],
+            tokenize(r#"r"a\nb\" "a\nb" r "" r"""#)
+        );
+        // Neither form of string may run to the end of the input
+        assert_eq!(
+            vec![token::Token::UnterminatedString, token::Token::Eof],
+            tokenize(r#"r"unterminated\"#)
+        );
+        assert_eq!(
+            vec![
+                token::Token::Let,
+                token::Token::Ident("s".to_string()),
+                token::Token::Assign,
+                token::Token::UnterminatedString,
+                token::Token::Eof,
+            ],
+            tokenize("let s = \"abc\nputs(s);\n")
+        );
+        assert_eq!(
+            vec![token::Token::UnterminatedString, token::Token::Eof],
+            tokenize(r#""unterminated\""#)
+        );
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        use crate::eval::object::Object;
+
+        for str in ["a\nb\"c", "tab\tand\r", "back\\slash", "\\d", "plain", ""] {
+            let escaped = Object::String(str.to_string()).escaped();
+            assert_eq!(
+                vec![token::Token::String(str.to_string()), token::Token::Eof],
+                tokenize(&escaped),
+                "escaped as {}",
+                escaped
+            );
+        }
+        assert_eq!(
+            Object::String("a\nb\"c".to_string()).escaped(),
+            r#""a\nb\"c""#
+        );
+        assert_eq!(Object::Integer(1).escaped(), "1");
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let expected = vec![
+            token::Token::Let,
+            token::Token::Ident("x".to_string()),
+            token::Token::Assign,
+            token::Token::Int(1),
+            token::Token::Semicolon,
+            token::Token::Eof,
+        ];
+        assert_eq!(expected, tokenize("let x = 1;"));
+        assert_eq!(vec![token::Token::Eof], tokenize(""));
+        assert_eq!(
+            vec![
+                token::Token::Int(2),
+                token::Token::Power,
+                token::Token::Int(3),
+                token::Token::Asterisk,
+                token::Token::Int(4),
+                token::Token::Eof,
+            ],
+            tokenize("2 ** 3 * 4")
+        );
+        assert_eq!(
+            vec![
+                token::Token::Int(9223372036854775807),
+                token::Token::IntTooLarge("9223372036854775808".to_string()),
+                token::Token::Eof,
+            ],
+            tokenize("9223372036854775807 9223372036854775808")
+        );
+        assert_eq!(
+            vec![
+                token::Token::Ellipsis,
+                token::Token::Ident("rest".to_string()),
+                token::Token::Illegal,
+                token::Token::Illegal,
+                token::Token::Eof,
+            ],
+            tokenize("...rest ..")
+        );
+        assert_eq!(
+            vec![
+                token::Token::Switch,
+                token::Token::Do,
+                token::Token::Int(1),
+                token::Token::FatArrow,
+                token::Token::Underscore,
+                token::Token::Assign,
+                token::Token::Eq,
+                token::Token::Eof,
+            ],
+            tokenize("switch do 1 => _ = ==")
+        );
+        assert_eq!(
+            vec![
+                token::Token::For,
+                token::Token::LParen,
+                token::Token::Ident("x".to_string()),
+                token::Token::In,
+                token::Token::Ident("xs".to_string()),
+                token::Token::RParen,
+                token::Token::Ident("inner".to_string()),
+                token::Token::Eof,
+            ],
+            tokenize("for (x in xs) inner")
+        );
+        assert_eq!(
+            vec![
+                token::Token::Break,
+                token::Token::Semicolon,
+                token::Token::Continue,
+                token::Token::Eof,
+            ],
+            tokenize("break; continue")
+        );
+    }
+
     #[test]
     fn mixed_chars() {
         let input = r#"let five = 5;
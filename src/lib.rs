@@ -1,6 +1,33 @@
 //! # The Monkey Programming Language Library
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use eval::{environment::Env, object::Object};
+
+pub mod error;
 pub mod eval;
+pub mod interpreter;
 pub mod lexer;
 pub mod parser;
 pub mod repl;
+pub mod symbol;
 pub mod token;
+
+/* Re-exports */
+pub use error::Error;
+pub use interpreter::Interpreter;
+
+/// Parse and evaluate the source code in a blank environment, returning the
+/// value of its last statement. Use an [`Interpreter`] to keep bindings across
+/// evaluations.
+///
+/// ```
+/// use monkey_rs::eval::object::Object;
+///
+/// assert_eq!(*monkey_rs::eval_expr("2 * 21").unwrap(), Object::Integer(42));
+/// ```
+pub fn eval_expr(src: &str) -> Result<Rc<Object>, Error> {
+    let program = parser::parse(src)?;
+    let env: Env = Rc::new(RefCell::new(Default::default()));
+    Ok(eval::eval(program, &env)?)
+}
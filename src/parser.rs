@@ -4,17 +4,22 @@
 Parses the input token stream into an AST and performs syntactic analysis.
 The parsing is accomplished via "top down operator precedence," also known
 as Pratt Parsing based off Vaughan Pratt's 1973 paper ["Top Down Operator
-Precdence"](https://dl.acm.org/doi/10.1145/512927.512931).
+Precedence"](https://dl.acm.org/doi/10.1145/512927.512931).
 */
 
 use crate::lexer;
+use crate::symbol::Symbol;
 use crate::token;
 
-pub(crate) mod ast;
+pub mod ast;
 pub mod error;
 pub mod precedence;
+mod pretty;
 
 /// Exposed function to parse a given input into a `ast::Node::Program`.
+///
+/// The returned AST can be inspected by tooling, e.g., linters or formatters,
+/// by matching on the nodes defined in [`ast`].
 pub fn parse(input: &str) -> Result<ast::Node, error::ParserError> {
     let mut lexer = lexer::Lexer::new(input);
     let mut parser = Parser::new(&mut lexer);
@@ -22,6 +27,19 @@ pub fn parse(input: &str) -> Result<ast::Node, error::ParserError> {
     Ok(ast::Node::Program(program))
 }
 
+/// Parse the input as [`parse`] does, except that a newline also separates
+/// statements where it would otherwise continue an expression, i.e., before a
+/// line starting with `(` or `[`. Each line of input such as `a\n(b)` is then
+/// its own statement rather than part of a call. A line starting with a binary
+/// operator still continues the expression of the line before it.
+pub fn parse_with_newlines(input: &str) -> Result<ast::Node, error::ParserError> {
+    let mut lexer = lexer::Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.newline_separators = true;
+    let program = parser.parse_program()?;
+    Ok(ast::Node::Program(program))
+}
+
 /// Parses the token stream into an AST.
 struct Parser<'a> {
     /// Lexer instance to read tokens from.
@@ -30,6 +48,17 @@ struct Parser<'a> {
     current_token: Option<token::Token>,
     /// The next token.
     peek_token: Option<token::Token>,
+    /// The position of the current token.
+    current_position: token::Position,
+    /// The position of the next token.
+    peek_position: token::Position,
+    /// Whether the next token starts a new line.
+    peek_newline: bool,
+    /// Whether a newline separates statements, see [`parse_with_newlines`].
+    newline_separators: bool,
+    /// The delimiters opened up to and including the current token that are
+    /// not yet closed, innermost last.
+    delimiters: Vec<token::Token>,
     /// Accrued parsing errors
     errors: Vec<error::ParserError>,
 }
@@ -41,6 +70,11 @@ impl<'a> Parser<'a> {
             lexer,
             current_token: None,
             peek_token: None,
+            current_position: token::Position::default(),
+            peek_position: token::Position::default(),
+            peek_newline: false,
+            newline_separators: false,
+            delimiters: Vec::new(),
             errors: Vec::new(),
         };
 
@@ -54,7 +88,41 @@ impl<'a> Parser<'a> {
     /// into the `peek` field.
     fn next_token(&mut self) {
         self.current_token = self.peek_token.take();
+        self.current_position = self.peek_position;
         self.peek_token = Some(self.lexer.next_token());
+        self.peek_position = self.lexer.token_position();
+        self.peek_newline = self.lexer.newline_before();
+
+        match self.current_token {
+            Some(
+                ref delimiter @ (token::Token::LParen
+                | token::Token::LBracket
+                | token::Token::LBrace),
+            ) => self.delimiters.push(delimiter.clone()),
+            Some(token::Token::RParen | token::Token::RBracket | token::Token::RBrace) => {
+                self.delimiters.pop();
+            }
+            _ => {}
+        }
+    }
+
+    /// Determine whether a newline separates the current token from the next
+    /// one, ending the expression of the current statement, when newlines
+    /// separate statements. Only a `(` or `[` starting a line is separated,
+    /// as it could start a new statement as well as continue a call or index
+    /// expression. Newlines within parentheses or brackets do not separate
+    /// anything, so that expressions may span lines there.
+    fn newline_separates(&self) -> bool {
+        self.newline_separators
+            && self.peek_newline
+            && matches!(
+                self.peek_token,
+                Some(token::Token::LParen | token::Token::LBracket)
+            )
+            && !matches!(
+                self.delimiters.last(),
+                Some(token::Token::LParen | token::Token::LBracket)
+            )
     }
 
     /// Determine whether the current token matches the specific token variant.
@@ -77,27 +145,28 @@ impl<'a> Parser<'a> {
             Err(error::ParserError::new(format!(
                 "Expected next token to be {:?}, received {:?}",
                 t, self.peek_token
-            )))
+            ))
+            .at(self.peek_position))
         }
     }
 
     /// Returns the precedence of the next token `self.peek`. If the next token
-    /// does not exist, then defaults to `Precdence::Lowest`. The returned
+    /// does not exist, then defaults to `Precedence::Lowest`. The returned
     /// precedence value corresponds to the left-binding power of the next
     /// token/operator in the token stream.
-    fn peek_precedence(&self) -> precedence::Precdence {
+    fn peek_precedence(&self) -> precedence::Precedence {
         match &self.peek_token {
             Some(token) => precedence::token_precedence(token),
-            None => precedence::Precdence::Lowest,
+            None => precedence::Precedence::Lowest,
         }
     }
 
     /// Returns the precedence of the current token `self.current_token`. If the
-    /// current token does not exist, then defaults to `Precdence::Lowest`.
-    fn curr_precedence(&self) -> precedence::Precdence {
+    /// current token does not exist, then defaults to `Precedence::Lowest`.
+    fn curr_precedence(&self) -> precedence::Precedence {
         match &self.current_token {
             Some(token) => precedence::token_precedence(token),
-            None => precedence::Precdence::Lowest,
+            None => precedence::Precedence::Lowest,
         }
     }
 
@@ -107,6 +176,20 @@ impl<'a> Parser<'a> {
         match self.current_token {
             Some(token::Token::Let) => self.parse_let_statement(),
             Some(token::Token::Return) => self.parse_return_statement(),
+            Some(token::Token::Break) => {
+                Ok(self.parse_loop_control_statement(ast::Statement::Break))
+            }
+            Some(token::Token::Continue) => {
+                Ok(self.parse_loop_control_statement(ast::Statement::Continue))
+            }
+            Some(token::Token::Ident(_)) if self.peek_token_is(&token::Token::Assign) => {
+                self.parse_assign_statement()
+            }
+            Some(token::Token::Function)
+                if matches!(self.peek_token, Some(token::Token::Ident(_))) =>
+            {
+                self.parse_function_statement()
+            }
             // Otherwise, default to parsing an expression statement.
             _ => self.parse_expression_statement(),
         }
@@ -124,12 +207,17 @@ impl<'a> Parser<'a> {
             }
         }
 
+        if self.peek_token_is(&token::Token::LBrace) {
+            return self.parse_let_hash_statement();
+        }
+
         let ident = match &self.peek_token {
-            Some(token::Token::Ident(ident)) => ident.clone(),
+            Some(token::Token::Ident(ident)) => Symbol::intern(ident),
             _ => {
-                return Err(error::ParserError::new(
-                    "Expected identifier after 'let'".to_string(),
-                ))
+                return Err(
+                    error::ParserError::new("Expected identifier after 'let'".to_string())
+                        .at(self.peek_position),
+                )
             }
         };
 
@@ -141,7 +229,7 @@ impl<'a> Parser<'a> {
         self.next_token();
 
         // Parse expression
-        let expr = self.parse_expression(precedence::Precdence::Lowest)?;
+        let expr = self.parse_expression(precedence::Precedence::Lowest)?;
 
         // Advance parser past the optional semicolon, if it exists
         if self.peek_token_is(&token::Token::Semicolon) {
@@ -151,6 +239,130 @@ impl<'a> Parser<'a> {
         Ok(ast::Statement::Let(ident, expr))
     }
 
+    /// Parses a let statement destructuring a hash, e.g.,
+    /// `let {name, age: years} = person;`, starting from the `let` token,
+    /// returning an AST node if successful, else a `ParserError`.
+    fn parse_let_hash_statement(&mut self) -> Result<ast::Statement, error::ParserError> {
+        // Consume the opening brace of the pattern
+        self.next_token();
+        let position = self.current_position;
+
+        let mut bindings = Vec::new();
+        while !self.peek_token_is(&token::Token::RBrace) {
+            let key = self.expect_peek_identifier("Expected identifier in hash pattern")?;
+
+            // A key may be bound to an identifier of another name
+            let ident = if self.peek_token_is(&token::Token::Colon) {
+                self.next_token();
+                self.expect_peek_identifier("Expected identifier after ':' in hash pattern")?
+            } else {
+                key
+            };
+            bindings.push((key, ident));
+
+            if !self.peek_token_is(&token::Token::RBrace) {
+                self.expect_peek_token(&token::Token::Comma)?;
+            }
+        }
+        self.expect_peek_token(&token::Token::RBrace)?;
+
+        self.expect_peek_token(&token::Token::Assign)?;
+        self.next_token();
+
+        let expr = self.parse_expression(precedence::Precedence::Lowest)?;
+
+        // Advance parser past the optional semicolon, if it exists
+        if self.peek_token_is(&token::Token::Semicolon) {
+            self.next_token();
+        }
+
+        Ok(ast::Statement::LetHash(bindings, expr, position))
+    }
+
+    /// Advances the parser past the next token if it is an identifier,
+    /// returning the identifier, else a `ParserError` with the given message.
+    fn expect_peek_identifier(&mut self, msg: &str) -> Result<Symbol, error::ParserError> {
+        let ident = match &self.peek_token {
+            Some(token::Token::Ident(ident)) => Symbol::intern(ident),
+            _ => return Err(error::ParserError::new(msg.to_string()).at(self.peek_position)),
+        };
+        self.next_token();
+        Ok(ident)
+    }
+
+    /// Parses an assignment statement, starting from the identifier being
+    /// assigned to, returning an AST node if successful, else a `ParserError`.
+    fn parse_assign_statement(&mut self) -> Result<ast::Statement, error::ParserError> {
+        let position = self.current_position;
+        let ident = match &self.current_token {
+            Some(token::Token::Ident(ident)) => Symbol::intern(ident),
+            _ => {
+                return Err(error::ParserError::new(
+                    "Expected identifier to assign to".to_string(),
+                ))
+            }
+        };
+
+        // Consume the assignment
+        self.expect_peek_token(&token::Token::Assign)?;
+        self.next_token();
+
+        // Parse expression
+        let expr = self.parse_expression(precedence::Precedence::Lowest)?;
+
+        // Advance parser past the optional semicolon, if it exists
+        if self.peek_token_is(&token::Token::Semicolon) {
+            self.next_token();
+        }
+
+        Ok(ast::Statement::Assign(ident, expr, position))
+    }
+
+    /// Parses a named function declaration, e.g., `fn add(a, b) { a + b }`,
+    /// into the equivalent let statement binding a function literal,
+    /// returning an AST node if successful, else a `ParserError`.
+    fn parse_function_statement(&mut self) -> Result<ast::Statement, error::ParserError> {
+        let ident = match &self.peek_token {
+            Some(token::Token::Ident(ident)) => Symbol::intern(ident),
+            _ => {
+                return Err(error::ParserError::new(
+                    "Expected identifier after 'fn'".to_string(),
+                ))
+            }
+        };
+
+        // Consume the identifier
+        self.next_token();
+
+        self.expect_peek_token(&token::Token::LParen)?;
+
+        // Parse the parameters and body of the function
+        let parameters = self.parse_function_parameters()?;
+        self.expect_peek_token(&token::Token::LBrace)?;
+        let body = self.parse_block_statement()?;
+
+        // Advance parser past the optional semicolon, if it exists
+        if self.peek_token_is(&token::Token::Semicolon) {
+            self.next_token();
+        }
+
+        Ok(ast::Statement::Let(
+            ident,
+            ast::Expression::Fn(parameters, body),
+        ))
+    }
+
+    /// Parses a `break` or `continue` statement from its keyword, returning
+    /// the given statement.
+    fn parse_loop_control_statement(&mut self, statement: ast::Statement) -> ast::Statement {
+        // Advance parser past the optional semicolon, if it exists
+        if self.peek_token_is(&token::Token::Semicolon) {
+            self.next_token();
+        }
+
+        statement
+    }
+
     /// Parses a return statement, returning an AST node if successful, else a
     /// `ParserError`.
     fn parse_return_statement(&mut self) -> Result<ast::Statement, error::ParserError> {
@@ -167,7 +379,7 @@ impl<'a> Parser<'a> {
         self.next_token();
 
         // Parse expression
-        let expr = self.parse_expression(precedence::Precdence::Lowest)?;
+        let expr = self.parse_expression(precedence::Precedence::Lowest)?;
 
         // Place parser after the semicolon, if it exists
         if self.peek_token_is(&token::Token::Semicolon) {
@@ -181,7 +393,7 @@ impl<'a> Parser<'a> {
     fn parse_expression_statement(&mut self) -> Result<ast::Statement, error::ParserError> {
         // Pass an initial lowest precedence since we haven't parse the rest of
         // the expression.
-        let expr = self.parse_expression(precedence::Precdence::Lowest)?;
+        let expr = self.parse_expression(precedence::Precedence::Lowest)?;
 
         // Check for optional semicolon, advancing past the semicolon
         // The semicolon is optional to allow expression statements such as
@@ -203,11 +415,10 @@ impl<'a> Parser<'a> {
                 break;
             }
 
+            let depth = self.delimiters.len();
             match self.parse_statement() {
                 Ok(stmt) => statements.push(stmt),
-                Err(err) => {
-                    self.errors.push(err);
-                }
+                Err(err) => self.recover(err, depth),
             }
             // Advance tokens
             self.next_token();
@@ -215,23 +426,54 @@ impl<'a> Parser<'a> {
 
         // Return a parsing error if any errors were encountered.
         if !self.errors.is_empty() {
-            // collect errors for display
-            let error_messages: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
-            return Err(error::ParserError::new(format!(
-                "Encountered {} error(s) while parsing:\n{}",
-                self.errors.len(),
-                error_messages.join("\n")
+            return Err(error::ParserError::collect(std::mem::take(
+                &mut self.errors,
             )));
         }
 
         Ok(statements)
     }
 
+    /// Records the error of a statement that started with the given number of
+    /// open delimiters, then recovers from it.
+    fn recover(&mut self, err: error::ParserError, depth: usize) {
+        // Errors not raised at a specific token are reported at the token
+        // parsing stopped at
+        self.errors.push(err.at(self.current_position));
+        self.synchronize(depth);
+    }
+
+    /// Recovers from a statement parse error by skipping tokens until the end
+    /// of the malformed statement, i.e., until the current token is a
+    /// semicolon or the next token begins a new statement or closes the
+    /// enclosing block. Delimiters opened within the statement are skipped
+    /// whole, given the number of delimiters open when it started. This
+    /// prevents a single malformed statement from cascading into spurious
+    /// errors.
+    fn synchronize(&mut self, depth: usize) {
+        // Stop if the error was raised at the brace closing the block
+        while self.delimiters.len() >= depth && !self.current_token_is(&token::Token::Eof) {
+            let in_statement = self.delimiters.len() == depth;
+            if in_statement && self.current_token_is(&token::Token::Semicolon) {
+                break;
+            }
+            match self.peek_token {
+                Some(token::Token::Eof) => break,
+                Some(token::Token::Let) | Some(token::Token::Return) if in_statement => break,
+                Some(token::Token::RBrace) if in_statement && depth > 0 => break,
+                _ => self.next_token(),
+            }
+        }
+    }
+
     /// Parses the current token as an identifier expression, else returns a
     /// parse error.
     fn parse_identifier(&self) -> Result<ast::Expression, error::ParserError> {
         match &self.current_token {
-            Some(token::Token::Ident(ident)) => Ok(ast::Expression::Identifier(ident.to_string())),
+            Some(token::Token::Ident(ident)) => Ok(ast::Expression::Identifier(
+                Symbol::intern(ident),
+                self.current_position,
+            )),
             _ => Err(error::ParserError::new("Expected identifier".to_string())),
         }
     }
@@ -240,6 +482,11 @@ impl<'a> Parser<'a> {
     fn parse_integer_literal(&self) -> Result<ast::Expression, error::ParserError> {
         match &self.current_token {
             Some(token::Token::Int(int)) => Ok(ast::Expression::Lit(ast::Literal::Integer(*int))),
+            Some(token::Token::IntTooLarge(literal)) => Err(error::ParserError::new(format!(
+                "integer literal too large: {}",
+                literal
+            ))
+            .at(self.current_position)),
             _ => Err(error::ParserError::new("Expected integer".to_string())),
         }
     }
@@ -253,13 +500,21 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Attempts to parse the current token as a null literal expression.
+    fn parse_null(&self) -> Result<ast::Expression, error::ParserError> {
+        match &self.current_token {
+            Some(token::Token::Null) => Ok(ast::Expression::Lit(ast::Literal::Null)),
+            _ => Err(error::ParserError::new("Expected null".to_string())),
+        }
+    }
+
     /// Attempts to parse a group expression, starting from the opening
     /// `token::Token::LParen` token.
     fn parse_grouped_expression(&mut self) -> Result<ast::Expression, error::ParserError> {
         // Advance past the opening left parenthesis
         self.next_token();
 
-        let expr = self.parse_expression(precedence::Precdence::Lowest)?;
+        let expr = self.parse_expression(precedence::Precedence::Lowest)?;
         self.expect_peek_token(&token::Token::RParen)?;
 
         Ok(expr)
@@ -281,7 +536,7 @@ impl<'a> Parser<'a> {
         self.next_token();
 
         // Parse condition expression
-        let condition = self.parse_expression(precedence::Precdence::Lowest)?;
+        let condition = self.parse_expression(precedence::Precedence::Lowest)?;
         self.expect_peek_token(&token::Token::RParen)?;
 
         // Parse consequence expression
@@ -291,8 +546,16 @@ impl<'a> Parser<'a> {
         // Parse alternative expression, if it exists
         let alternative = if self.peek_token_is(&token::Token::Else) {
             self.next_token();
-            self.expect_peek_token(&token::Token::LBrace)?;
-            Some(self.parse_block_statement()?)
+            if self.peek_token_is(&token::Token::If) {
+                // An `else if` chain is parsed as an alternative block whose
+                // only statement is the nested if-expression.
+                self.next_token();
+                let nested = self.parse_if_expression()?;
+                Some(vec![ast::Statement::Expr(nested)])
+            } else {
+                self.expect_peek_token(&token::Token::LBrace)?;
+                Some(self.parse_block_statement()?)
+            }
         } else {
             None
         };
@@ -304,6 +567,83 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    /// Parses the do expression from the current token, returning an
+    /// `ast::Expression::Do(...)` node of the statements of its block.
+    fn parse_do_expression(&mut self) -> Result<ast::Expression, error::ParserError> {
+        self.expect_peek_token(&token::Token::LBrace)?;
+        let body = self.parse_block_statement()?;
+        Ok(ast::Expression::Do(body))
+    }
+
+    /// Parses the for-in expression from the current token, returning an
+    /// `ast::Expression::For(...)` node of the bound identifier, the iterated
+    /// expression, and the statements of its block.
+    fn parse_for_expression(&mut self) -> Result<ast::Expression, error::ParserError> {
+        self.expect_peek_token(&token::Token::LParen)?;
+        let ident = self.expect_peek_identifier("Expected identifier after 'for ('")?;
+        self.expect_peek_token(&token::Token::In)?;
+        self.next_token();
+
+        let position = self.current_position;
+        let iterable = self.parse_expression(precedence::Precedence::Lowest)?;
+        self.expect_peek_token(&token::Token::RParen)?;
+
+        self.expect_peek_token(&token::Token::LBrace)?;
+        let body = self.parse_block_statement()?;
+
+        Ok(ast::Expression::For(
+            ident,
+            Box::new(iterable),
+            body,
+            position,
+        ))
+    }
+
+    /// Parses the switch expression from the current token, returning an
+    /// `ast::Expression::Switch(...)` node of the subject and the arms.
+    fn parse_switch_expression(&mut self) -> Result<ast::Expression, error::ParserError> {
+        self.expect_peek_token(&token::Token::LParen)?;
+        self.next_token();
+
+        let position = self.current_position;
+        let subject = self.parse_expression(precedence::Precedence::Lowest)?;
+        self.expect_peek_token(&token::Token::RParen)?;
+        self.expect_peek_token(&token::Token::LBrace)?;
+
+        let mut arms = Vec::new();
+        while !self.peek_token_is(&token::Token::RBrace) {
+            // Arms after the wildcard could never be reached, so report the
+            // first of them
+            if matches!(arms.last(), Some((ast::Pattern::Wildcard, _))) {
+                return Err(error::ParserError::new(
+                    "Wildcard pattern '_' must be the last arm of a switch".to_string(),
+                )
+                .at(self.peek_position));
+            }
+            self.next_token();
+
+            let pattern = if self.current_token_is(&token::Token::Underscore) {
+                ast::Pattern::Wildcard
+            } else {
+                ast::Pattern::Value(self.parse_expression(precedence::Precedence::Lowest)?)
+            };
+
+            self.expect_peek_token(&token::Token::FatArrow)?;
+            self.next_token();
+
+            let value = self.parse_expression(precedence::Precedence::Lowest)?;
+            arms.push((pattern, value));
+
+            if !self.peek_token_is(&token::Token::RBrace) {
+                self.expect_peek_token(&token::Token::Comma)?;
+            }
+        }
+
+        self.expect_peek_token(&token::Token::RBrace)?;
+
+        Ok(ast::Expression::Switch(Box::new(subject), arms, position))
+    }
+
     /// Parses the block statement from the current token, which should be on
     /// the opening curly left brace.
     fn parse_block_statement(&mut self) -> Result<ast::BlockStatement, error::ParserError> {
@@ -319,18 +659,34 @@ impl<'a> Parser<'a> {
         self.next_token();
 
         let mut block_statement = Vec::new();
+        let depth = self.delimiters.len();
 
         // Continue to parse statement until we either reach the end of the
-        // block statement or EOF.
+        // block statement or EOF. Errors are recorded like those of the
+        // program's statements, failing the parse once it completes.
         while !self.current_token_is(&token::Token::RBrace)
             && !self.current_token_is(&token::Token::Eof)
         {
-            if let Ok(stmt) = self.parse_statement() {
-                block_statement.push(stmt);
+            match self.parse_statement() {
+                Ok(stmt) => block_statement.push(stmt),
+                Err(err) => {
+                    self.recover(err, depth);
+                    // The error was raised at the brace closing the block
+                    if self.delimiters.len() < depth {
+                        break;
+                    }
+                }
             }
             self.next_token();
         }
 
+        if self.current_token_is(&token::Token::Eof) {
+            return Err(error::ParserError::new(
+                "Expected '}' to close the block, reached end of input".to_string(),
+            )
+            .at(self.current_position));
+        }
+
         Ok(block_statement)
     }
 
@@ -356,21 +712,59 @@ impl<'a> Parser<'a> {
     }
 
     /// Parses the parameters of a function literal expression.
-    fn parse_function_parameters(&mut self) -> Result<Vec<String>, error::ParserError> {
-        let mut identifiers = Vec::new();
+    fn parse_function_parameters(&mut self) -> Result<Vec<ast::Parameter>, error::ParserError> {
+        let mut parameters = Vec::new();
 
         // Early exit in the case of no parameters/ empty list
         if self.peek_token_is(&token::Token::RParen) {
             self.next_token();
-            return Ok(identifiers);
+            return Ok(parameters);
         }
 
         // Advance past the opening left parenthesis
         self.next_token();
 
-        // Add the current identifier
-        match &self.current_token {
-            Some(token::Token::Ident(ref param)) => identifiers.push(param.clone()),
+        // Add the current parameter
+        parameters.push(self.parse_function_parameter(&parameters)?);
+
+        // Add parameter(s) following the first parameter, if they exist,
+        // allowing a trailing comma before the closing parenthesis
+        while self.peek_token_is(&token::Token::Comma) {
+            self.next_token();
+            if self.peek_token_is(&token::Token::RParen) {
+                break;
+            }
+            self.next_token();
+            parameters.push(self.parse_function_parameter(&parameters)?);
+        }
+
+        self.expect_peek_token(&token::Token::RParen)?;
+
+        Ok(parameters)
+    }
+
+    /// Parses a single function parameter from the current token, which
+    /// should be its identifier or the ellipsis of a rest parameter, given the
+    /// parameters parsed before it.
+    fn parse_function_parameter(
+        &mut self,
+        preceding: &[ast::Parameter],
+    ) -> Result<ast::Parameter, error::ParserError> {
+        // No parameter may follow a rest parameter
+        if let Some(rest @ ast::Parameter::Rest(_)) = preceding.last() {
+            return Err(error::ParserError::new(format!(
+                "Rest parameter '{}' must be the last parameter",
+                rest
+            )));
+        }
+
+        let is_rest = self.current_token_is(&token::Token::Ellipsis);
+        if is_rest {
+            self.next_token();
+        }
+
+        let name = match &self.current_token {
+            Some(token::Token::Ident(ref param)) => Symbol::intern(param),
             Some(token) => {
                 return Err(error::ParserError::new(format!(
                     "Expected a parameter identifer, got {}",
@@ -382,32 +776,32 @@ impl<'a> Parser<'a> {
                     "Expected a parameter identifer, received None".to_string(),
                 ))
             }
+        };
+
+        if is_rest {
+            return Ok(ast::Parameter::Rest(name));
         }
 
-        // Add parameter identifier(s) following the first parameter, if they
-        // exist
-        while self.peek_token_is(&token::Token::Comma) {
+        // Parse the default value of the parameter, if it exists
+        if self.peek_token_is(&token::Token::Assign) {
             self.next_token();
             self.next_token();
-            match &self.current_token {
-                Some(token::Token::Ident(ref param)) => identifiers.push(param.clone()),
-                Some(token) => {
-                    return Err(error::ParserError::new(format!(
-                        "Expected a parameter identifer, got {}",
-                        token
-                    )))
-                }
-                None => {
-                    return Err(error::ParserError::new(
-                        "Expected a parameter identifer, received None".to_string(),
-                    ))
-                }
-            }
+            let default = self.parse_expression(precedence::Precedence::Lowest)?;
+            return Ok(ast::Parameter::Default(name, default));
         }
 
-        self.expect_peek_token(&token::Token::RParen)?;
+        // Defaults may only be given to the trailing parameters
+        if preceding
+            .iter()
+            .any(|param| matches!(param, ast::Parameter::Default(..)))
+        {
+            return Err(error::ParserError::new(format!(
+                "Required parameter '{}' cannot follow a parameter with a default value",
+                name
+            )));
+        }
 
-        Ok(identifiers)
+        Ok(ast::Parameter::Required(name))
     }
 
     /// Parse the function call expression from the current token.
@@ -415,22 +809,98 @@ impl<'a> Parser<'a> {
         &mut self,
         expr: ast::Expression,
     ) -> Result<ast::Expression, error::ParserError> {
-        let args = self.parse_expression_list(&token::Token::RParen)?;
-        Ok(ast::Expression::Call(Box::new(expr), args))
+        let position = self.current_position;
+        let args = self.parse_call_arguments()?;
+        Ok(ast::Expression::Call(Box::new(expr), args, position))
+    }
+
+    /// Parse a comma-separated list of call arguments until the closing
+    /// parenthesis is the next token.
+    fn parse_call_arguments(&mut self) -> Result<Vec<ast::Argument>, error::ParserError> {
+        let mut args = Vec::new();
+
+        if self.peek_token_is(&token::Token::RParen) {
+            self.next_token();
+            return Ok(args);
+        }
+
+        self.next_token();
+        args.push(self.parse_call_argument(&args)?);
+
+        // Allow a trailing comma before the closing parenthesis
+        while self.peek_token_is(&token::Token::Comma) {
+            self.next_token();
+            if self.peek_token_is(&token::Token::RParen) {
+                break;
+            }
+            self.next_token();
+            args.push(self.parse_call_argument(&args)?);
+        }
+
+        self.expect_peek_token(&token::Token::RParen)?;
+
+        Ok(args)
+    }
+
+    /// Parse a single call argument from the current token, given the
+    /// arguments parsed before it. An identifier followed by a colon begins a
+    /// keyword argument, e.g., `x: 1`.
+    fn parse_call_argument(
+        &mut self,
+        preceding: &[ast::Argument],
+    ) -> Result<ast::Argument, error::ParserError> {
+        if let Some(token::Token::Ident(name)) = &self.current_token {
+            if self.peek_token_is(&token::Token::Colon) {
+                let name = Symbol::intern(name);
+                self.next_token();
+                self.next_token();
+                let expr = self.parse_expression(precedence::Precedence::Lowest)?;
+                return Ok(ast::Argument::Keyword(name, expr));
+            }
+        }
+
+        let expr = self.parse_expression(precedence::Precedence::Lowest)?;
+
+        // Positional arguments may not follow keyword arguments
+        if preceding
+            .iter()
+            .any(|arg| matches!(arg, ast::Argument::Keyword(..)))
+        {
+            return Err(error::ParserError::new(format!(
+                "Positional argument '{}' cannot follow a keyword argument",
+                expr
+            )));
+        }
+
+        Ok(ast::Argument::Positional(expr))
     }
 
     /// Attempts to parse the current token as a prefix expression.
     fn parse_prefix_expression(&mut self) -> Result<ast::Expression, error::ParserError> {
         let prefix = self.current_token.clone();
+        let position = self.current_position;
 
         // advance the parser
         self.next_token();
 
-        let expr = self.parse_expression(precedence::Precdence::Prefix)?;
+        // The literal of the smallest integer is only in range once negated,
+        // so it is too large on its own
+        if prefix == Some(token::Token::Minus)
+            && matches!(
+                &self.current_token,
+                Some(token::Token::IntTooLarge(literal))
+                    if *literal == i64::MIN.unsigned_abs().to_string()
+            )
+        {
+            return Ok(ast::Expression::Lit(ast::Literal::Integer(i64::MIN)));
+        }
+
+        let expr = self.parse_expression(precedence::Precedence::Prefix)?;
 
         Ok(ast::Expression::Prefix(
             prefix.expect("Expected a prefix token"),
             Box::new(expr),
+            position,
         ))
     }
 
@@ -442,7 +912,13 @@ impl<'a> Parser<'a> {
     ) -> Result<ast::Expression, error::ParserError> {
         // Handle the infix operator
         let operator = self.current_token.clone();
-        let precedence = self.curr_precedence();
+        let position = self.current_position;
+        let precedence = match operator {
+            // `**` is right-associative, so its right operand is parsed with a
+            // lower precedence to bind any further `**` operators first.
+            Some(token::Token::Power) => precedence::Precedence::Product,
+            _ => self.curr_precedence(),
+        };
         self.next_token();
 
         // Parse the right expression
@@ -452,6 +928,7 @@ impl<'a> Parser<'a> {
             operator.expect("Expected infix operator"),
             Box::new(left),
             Box::new(right),
+            position,
         ))
     }
 
@@ -460,17 +937,25 @@ impl<'a> Parser<'a> {
     /// invocation.
     fn parse_expression(
         &mut self,
-        precedence: precedence::Precdence,
+        precedence: precedence::Precedence,
     ) -> Result<ast::Expression, error::ParserError> {
         let mut left_expr = match self.current_token {
             Some(token::Token::True) | Some(token::Token::False) => self.parse_boolean(),
+            Some(token::Token::Null) => self.parse_null(),
             Some(token::Token::Ident(_)) => self.parse_identifier(),
-            Some(token::Token::Int(_)) => self.parse_integer_literal(),
+            Some(token::Token::Int(_)) | Some(token::Token::IntTooLarge(_)) => {
+                self.parse_integer_literal()
+            }
             Some(token::Token::Bang) | Some(token::Token::Minus) => self.parse_prefix_expression(),
             Some(token::Token::LParen) => self.parse_grouped_expression(),
             Some(token::Token::If) => self.parse_if_expression(),
+            Some(token::Token::Switch) => self.parse_switch_expression(),
+            Some(token::Token::Do) => self.parse_do_expression(),
+            Some(token::Token::For) => self.parse_for_expression(),
             Some(token::Token::Function) => self.parse_function_literal(),
-            Some(token::Token::String(_)) => self.parse_string_literal(),
+            Some(token::Token::String(_)) | Some(token::Token::UnterminatedString) => {
+                self.parse_string_literal()
+            }
             Some(token::Token::LBracket) => self.parse_array_literal(),
             Some(token::Token::LBrace) => self.parse_hash_literal(),
             _ => Err(error::ParserError::new(format!(
@@ -485,14 +970,39 @@ impl<'a> Parser<'a> {
         //
         // NOTE: The check for the peek token being a semicolon is not strictly
         // necessary since the `peek_precedence` method will default to
-        // returning `Precdence::Lowest`. However, this explicitly sets the
+        // returning `Precedence::Lowest`. However, this explicitly sets the
         // semantic behavior of semicolons and expression-ending delimiters.
-        while !self.peek_token_is(&token::Token::Semicolon) && precedence < self.peek_precedence() {
+        // When newlines separate statements, a newline before a `(` or `[`
+        // ends the expression too, so that `a\n(b)` is two statements rather
+        // than a call.
+        //
+        // `after_comparison` is whether the last operator parsed by this loop
+        // is a comparison, to reject chained comparisons such as `a < b < c`,
+        // which would otherwise compare the boolean result of `a < b` to `c`.
+        let mut after_comparison = false;
+        while !self.peek_token_is(&token::Token::Semicolon)
+            && !self.newline_separates()
+            && precedence < self.peek_precedence()
+        {
+            let comparison = matches!(
+                self.peek_token,
+                Some(token::Token::Lt) | Some(token::Token::Gt)
+            );
+            if comparison && after_comparison {
+                return Err(error::ParserError::new(
+                    "Comparisons cannot be chained, e.g., `a < b < c`; compare each pair separately"
+                        .to_string(),
+                )
+                .at(self.peek_position));
+            }
+            after_comparison = comparison;
+
             match self.peek_token {
                 Some(token::Token::Plus)
                 | Some(token::Token::Minus)
                 | Some(token::Token::Slash)
                 | Some(token::Token::Asterisk)
+                | Some(token::Token::Power)
                 | Some(token::Token::Eq)
                 | Some(token::Token::NotEq)
                 | Some(token::Token::Lt)
@@ -531,6 +1041,10 @@ impl<'a> Parser<'a> {
     /// Parse the string literal from the current token.
     fn parse_string_literal(&self) -> Result<ast::Expression, error::ParserError> {
         match &self.current_token {
+            Some(token::Token::UnterminatedString) => Err(error::ParserError::new(
+                "unterminated string literal".to_string(),
+            )
+            .at(self.current_position)),
             Some(ref str) => Ok(ast::Expression::Lit(ast::Literal::String(str.to_string()))),
             None => Err(error::ParserError::new(
                 "expected string literal".to_string(),
@@ -558,12 +1072,16 @@ impl<'a> Parser<'a> {
         }
 
         self.next_token();
-        list.push(self.parse_expression(precedence::Precdence::Lowest)?);
+        list.push(self.parse_expression(precedence::Precedence::Lowest)?);
 
+        // Allow a trailing comma before the ending token
         while self.peek_token_is(&token::Token::Comma) {
             self.next_token();
+            if self.peek_token_is(end) {
+                break;
+            }
             self.next_token();
-            list.push(self.parse_expression(precedence::Precdence::Lowest)?);
+            list.push(self.parse_expression(precedence::Precedence::Lowest)?);
         }
 
         self.expect_peek_token(end)?;
@@ -571,20 +1089,62 @@ impl<'a> Parser<'a> {
         Ok(list)
     }
 
-    /// Parse the index expression from the current token.
+    /// Parse the index expression from the current token. If the brackets
+    /// contain a colon, e.g., `s[1:3]`, a slice expression is parsed instead.
     fn parse_index_expresssion(
         &mut self,
         left_expr: ast::Expression,
     ) -> Result<ast::Expression, error::ParserError> {
+        let position = self.current_position;
         self.next_token();
 
-        let index_expr = self.parse_expression(precedence::Precdence::Lowest)?;
+        // Slice with an omitted start bound, e.g., `s[:2]`
+        if self.current_token_is(&token::Token::Colon) {
+            return self.parse_slice_expression(left_expr, None, position);
+        }
+
+        let index_expr = self.parse_expression(precedence::Precedence::Lowest)?;
+
+        if self.peek_token_is(&token::Token::Colon) {
+            self.next_token();
+            return self.parse_slice_expression(left_expr, Some(Box::new(index_expr)), position);
+        }
 
         self.expect_peek_token(&token::Token::RBracket)?;
 
         Ok(ast::Expression::Index(
             Box::new(left_expr),
             Box::new(index_expr),
+            position,
+        ))
+    }
+
+    /// Parse the remainder of a slice expression from the current token, which
+    /// should be the colon separating the start and end bounds, given the
+    /// position of the opening bracket.
+    fn parse_slice_expression(
+        &mut self,
+        left_expr: ast::Expression,
+        start: Option<Box<ast::Expression>>,
+        position: token::Position,
+    ) -> Result<ast::Expression, error::ParserError> {
+        // Slice with an omitted end bound, e.g., `s[2:]`
+        let end = if self.peek_token_is(&token::Token::RBracket) {
+            None
+        } else {
+            self.next_token();
+            Some(Box::new(
+                self.parse_expression(precedence::Precedence::Lowest)?,
+            ))
+        };
+
+        self.expect_peek_token(&token::Token::RBracket)?;
+
+        Ok(ast::Expression::Slice(
+            Box::new(left_expr),
+            start,
+            end,
+            position,
         ))
     }
 
@@ -594,12 +1154,12 @@ impl<'a> Parser<'a> {
         while !self.peek_token_is(&token::Token::RBrace) {
             self.next_token();
 
-            let key = self.parse_expression(precedence::Precdence::Lowest)?;
+            let key = self.parse_expression(precedence::Precedence::Lowest)?;
 
             self.expect_peek_token(&token::Token::Colon)?;
             self.next_token();
 
-            let value = self.parse_expression(precedence::Precdence::Lowest)?;
+            let value = self.parse_expression(precedence::Precedence::Lowest)?;
 
             hash.push((key, value));
 
@@ -653,22 +1213,49 @@ mod tests {
         }
 
         let expected = vec![
+            ast::Statement::Let("x".into(), ast::Expression::Lit(ast::Literal::Integer(5))),
+            ast::Statement::Let("y".into(), ast::Expression::Lit(ast::Literal::Integer(10))),
             ast::Statement::Let(
-                "x".to_string(),
-                ast::Expression::Lit(ast::Literal::Integer(5)),
-            ),
-            ast::Statement::Let(
-                "y".to_string(),
-                ast::Expression::Lit(ast::Literal::Integer(10)),
-            ),
-            ast::Statement::Let(
-                "foobar".to_string(),
+                "foobar".into(),
                 ast::Expression::Lit(ast::Literal::Integer(838383)),
             ),
         ];
         assert_eq!(expected, program)
     }
 
+    #[test]
+    fn test_assign_statement() {
+        let cases = [
+            ("x = 5;", "x = 5;"),
+            ("x = x * 2", "x = (x * 2);"),
+            ("x == 5", "(x == 5)"),
+        ];
+        check_parse_test_cases(&cases);
+    }
+
+    #[test]
+    fn test_let_hash_statement() {
+        let cases = [
+            ("let {name, age} = person;", "let {name, age} = person;"),
+            (
+                "let {name: n, age,} = person",
+                "let {name: n, age} = person;",
+            ),
+            ("let {} = {}", "let {} = {};"),
+        ];
+        check_parse_test_cases(&cases);
+        check_pretty_test_cases(&[("let {a: b} = {\"a\": 1}; b", "let {a: b} = {\"a\": 1};\nb;")]);
+
+        let err = parse("let {\"name\"} = person;").unwrap_err();
+        let err = &err.errors()[0];
+        assert_eq!(err.to_string(), "Expected identifier in hash pattern");
+        assert_eq!(
+            err.position().map(|p| p.to_string()).as_deref(),
+            Some("1:6")
+        );
+        assert!(parse("let {name: 1} = person;").is_err());
+    }
+
     #[test]
     fn test_invalid_let_statement() {
         let input = "let x 5;";
@@ -676,6 +1263,29 @@ mod tests {
         let mut p = Parser::new(&mut l);
         let program = p.parse_program();
         assert!(&program.is_err());
+
+        // Keywords cannot be bound as identifiers
+        for (keyword, _) in token::KEYWORDS {
+            let input = format!("let {} = 1;", keyword);
+            assert!(parse(&input).is_err(), "{} should not parse", input);
+        }
+    }
+
+    #[test]
+    fn test_multiple_parse_errors() {
+        let input = "let = 5 * (2 + 3); let y 10; let z = 1;";
+        let mut l = lexer::Lexer::new(input);
+        let mut p = Parser::new(&mut l);
+        let err = p.parse_program().expect_err("expected parse errors");
+        let msg = err.to_string();
+
+        assert!(
+            msg.starts_with("Encountered 2 error(s) while parsing:"),
+            "unexpected error message: {}",
+            msg
+        );
+        assert!(msg.contains("Expected identifier after 'let'"));
+        assert!(msg.contains("Expected next token to be Assign, received Some(Int(10))"));
     }
 
     #[test]
@@ -711,8 +1321,8 @@ mod tests {
         assert!(program.is_ok());
         let program = ast::Node::Program(program.unwrap());
         let expected = ast::Node::Program(vec![ast::Statement::Let(
-            "myVar".to_string(),
-            ast::Expression::Identifier("anotherVar".to_string()),
+            "myVar".into(),
+            ast::Expression::Identifier("anotherVar".into(), token::Position::new(1, 13)),
         )]);
         assert_eq!(expected, program);
     }
@@ -725,7 +1335,8 @@ mod tests {
         let program = p.parse_program().unwrap();
         assert_eq!(program.len(), 1);
         let expected = vec![ast::Statement::Expr(ast::Expression::Identifier(
-            "foobar".to_string(),
+            "foobar".into(),
+            token::Position::new(1, 1),
         ))];
         assert_eq!(expected, program);
     }
@@ -741,6 +1352,29 @@ mod tests {
             ast::Literal::Integer(5),
         ))];
         assert_eq!(expected, program);
+
+        // Literals that do not fit in 64 bits are reported where they start
+        let err = parse("let x = 1;\nlet y = 99999999999999999999;").unwrap_err();
+        let err = &err.errors()[0];
+        assert_eq!(
+            err.to_string(),
+            "integer literal too large: 99999999999999999999"
+        );
+        assert_eq!(err.position(), Some(token::Position::new(2, 9)));
+
+        // ... except for the smallest integer, which is in range once negated
+        let program = parse("-9223372036854775808").unwrap();
+        assert_eq!(
+            program,
+            ast::Node::Program(vec![ast::Statement::Expr(ast::Expression::Lit(
+                ast::Literal::Integer(i64::MIN)
+            ))])
+        );
+        let err = parse("-9223372036854775809").unwrap_err();
+        assert_eq!(
+            err.errors()[0].to_string(),
+            "integer literal too large: 9223372036854775809"
+        );
     }
 
     #[test]
@@ -749,6 +1383,12 @@ mod tests {
         check_parse_test_cases(&bool_tests);
     }
 
+    #[test]
+    fn test_null_expressions() {
+        let null_tests = [("null", "null"), ("x == null", "(x == null)")];
+        check_parse_test_cases(&null_tests);
+    }
+
     #[test]
     fn test_parsing_prefix_expressions() {
         let prefix_cases = [
@@ -822,6 +1462,10 @@ mod tests {
                 "add(a * b[2], b[1], 2 * [1, 2][1])",
                 "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
             ),
+            ("2 ** 2 ** 3", "(2 ** (2 ** 3))"),
+            ("a * b ** c", "(a * (b ** c))"),
+            ("a ** b * c", "((a ** b) * c)"),
+            ("-a ** b", "((-a) ** b)"),
         ];
         check_parse_test_cases(&precedence_tests);
     }
@@ -838,6 +1482,21 @@ mod tests {
         check_parse_test_cases(&ifelse_case);
     }
 
+    #[test]
+    fn test_else_if_expression() {
+        let cases = [
+            (
+                "if (a) { x } else if (b) { y }",
+                "if a { x } else { if b { y } }",
+            ),
+            (
+                "if (a) { x } else if (b) { y } else { z }",
+                "if a { x } else { if b { y } else { z } }",
+            ),
+        ];
+        check_parse_test_cases(&cases);
+    }
+
     #[test]
     fn test_if_else_function_literal() {
         let cases = [
@@ -867,6 +1526,74 @@ mod tests {
         check_parse_test_cases(&fn_params_cases);
     }
 
+    #[test]
+    fn test_default_parameters() {
+        let cases = [
+            ("fn(x, y = 10) { x + y };", "fn(x, y = 10) { (x + y) }"),
+            ("fn(x = 1 + 2,) { x };", "fn(x = (1 + 2)) { x }"),
+        ];
+        check_parse_test_cases(&cases);
+
+        let err = parse("fn(x = 1, y) { x }").expect_err("expected a parse error");
+        assert!(err
+            .to_string()
+            .contains("Required parameter 'y' cannot follow a parameter with a default value"));
+    }
+
+    #[test]
+    fn test_rest_parameters() {
+        let cases = [
+            (
+                "fn(first, ...rest) { rest };",
+                "fn(first, ...rest) { rest }",
+            ),
+            ("fn(...args,) { args };", "fn(...args) { args }"),
+        ];
+        check_parse_test_cases(&cases);
+
+        for (input, msg) in [
+            (
+                "fn(...a, b) { a }",
+                "Rest parameter '...a' must be the last parameter",
+            ),
+            (
+                "fn(...a, ...b) { a }",
+                "Rest parameter '...a' must be the last parameter",
+            ),
+            ("fn(...) { 1 }", "Expected a parameter identifer, got )"),
+        ] {
+            let err = parse(input).expect_err("expected a parse error");
+            assert!(err.to_string().contains(msg), "unexpected error: {}", err);
+        }
+    }
+
+    #[test]
+    fn test_keyword_arguments() {
+        let cases = [
+            ("f(1, y: 2 * 3)", "f(1, y: (2 * 3))"),
+            ("f(x: {\"a\": 1}[\"a\"],)", "f(x: ({\"a\": 1}[\"a\"]))"),
+        ];
+        check_parse_test_cases(&cases);
+
+        let err = parse("f(x: 1, 2)").expect_err("expected a parse error");
+        assert!(err
+            .to_string()
+            .contains("Positional argument '2' cannot follow a keyword argument"));
+    }
+
+    #[test]
+    fn test_function_statement() {
+        let cases = [
+            (
+                "fn add(a, b) { a + b } add(2, 3)",
+                "let add = fn(a, b) { (a + b) };add(2, 3)",
+            ),
+            ("fn nothing() {};", "let nothing = fn() {  };"),
+            ("fn(x) { x }(5)", "fn(x) { x }(5)"),
+        ];
+        check_parse_test_cases(&cases);
+    }
+
     #[test]
     fn test_call_expression_parsing() {
         let fn_call_cases = [("add(1, 2 * 3, 4 + 5)", "add(1, (2 * 3), (4 + 5))")];
@@ -877,6 +1604,15 @@ mod tests {
     fn test_string_literal_expression() {
         let str_lit_cases = [("\"hello world\";", "\"hello world\"")];
         check_parse_test_cases(&str_lit_cases);
+
+        // Strings missing their closing quotation are reported where they
+        // start, rather than running to the end of the input
+        for input in ["let s = \"abc\nputs(s);\n", "let s = r\"abc\nputs(s);\n"] {
+            let err = parse(input).unwrap_err();
+            let err = &err.errors()[0];
+            assert_eq!(err.to_string(), "unterminated string literal");
+            assert_eq!(err.position(), Some(token::Position::new(1, 9)));
+        }
     }
 
     #[test]
@@ -891,6 +1627,295 @@ mod tests {
         check_parse_test_cases(&case);
     }
 
+    #[test]
+    fn test_parsing_slice_expressions() {
+        let cases = [
+            ("s[1:3]", "(s[1:3])"),
+            ("s[:2]", "(s[:2])"),
+            ("s[2:]", "(s[2:])"),
+            ("s[:]", "(s[:])"),
+            ("s[a + 1:len(s) - 1]", "(s[(a + 1):(len(s) - 1)])"),
+        ];
+        check_parse_test_cases(&cases);
+    }
+
+    #[test]
+    fn test_switch_expression() {
+        let cases = [
+            (
+                r#"switch (x) { 1 => "one", 2 => "two", _ => "other" }"#,
+                r#"switch x { 1 => "one", 2 => "two", _ => "other" }"#,
+            ),
+            (
+                "switch (a + b) { c * 2 => d, }",
+                "switch (a + b) { (c * 2) => d }",
+            ),
+            ("switch (x) {}", "switch x {  }"),
+            (
+                "let y = switch (x) { _ => 0 };",
+                "let y = switch x { _ => 0 };",
+            ),
+        ];
+        check_parse_test_cases(&cases);
+
+        for (input, expected) in [
+            (
+                "switch (x) { _ => 0, 1 => 1 }",
+                "Wildcard pattern '_' must be the last arm of a switch",
+            ),
+            ("switch (x) { 1 2 }", "FatArrow"),
+            ("switch (x) { 1 => 1 2 => 2 }", "Comma"),
+        ] {
+            let err = parse(input).unwrap_err().to_string();
+            assert!(
+                err.contains(expected),
+                "{} should contain {}",
+                err,
+                expected
+            );
+        }
+
+        let err = parse("switch (x) {\n  _ => 0,\n  1 => 1\n}").unwrap_err();
+        assert_eq!(err.errors()[0].position(), Some(token::Position::new(3, 3)));
+    }
+
+    #[test]
+    fn test_do_expression() {
+        let cases = [
+            (
+                "let y = do { let z = 5; z * 2 };",
+                "let y = do { let z = 5;(z * 2) };",
+            ),
+            ("do {} + 1", "(do {  } + 1)"),
+        ];
+        check_parse_test_cases(&cases);
+
+        let err = parse("do 1").unwrap_err().to_string();
+        assert!(err.contains("LBrace"), "{} should contain LBrace", err);
+    }
+
+    #[test]
+    fn test_for_expression() {
+        let cases = [
+            (
+                "for (x in [1, 2]) { puts(x); }",
+                "for x in [1, 2] { puts(x) }",
+            ),
+            ("for (x in xs) {}", "for x in xs {  }"),
+        ];
+        check_parse_test_cases(&cases);
+        check_pretty_test_cases(&[(
+            "for (x in f(y)) { total = total + x }",
+            "for (x in f(y)) {\n  total = total + x;\n};",
+        )]);
+
+        for input in [
+            "for x in xs { x }",
+            "for (1 in xs) { x }",
+            "for (x xs) { x }",
+        ] {
+            assert!(parse(input).is_err(), "{} should not parse", input);
+        }
+    }
+
+    #[test]
+    fn test_break_and_continue_statements() {
+        let cases = [
+            (
+                "for (x in xs) { if (x) { break; } continue }",
+                "for x in xs { if x { break; }continue; }",
+            ),
+            ("break continue", "break;continue;"),
+        ];
+        check_parse_test_cases(&cases);
+        check_pretty_test_cases(&[(
+            "for (x in xs) { continue; }",
+            "for (x in xs) {\n  continue;\n};",
+        )]);
+    }
+
+    #[test]
+    fn test_block_statement_errors() {
+        let cases = [
+            ("if (x) { let x 5 }", "Expected next token to be Assign"),
+            (
+                "if (x) { 1 } else { let = 2; }",
+                "Expected identifier after 'let'",
+            ),
+            (
+                "let f = fn() { let = 1; 2 }; let g = 3",
+                "Expected identifier",
+            ),
+            (
+                "if (x) { 1 + }",
+                "No prefix parse function for Some(RBrace)",
+            ),
+            (
+                "if (x) { let a = [1 2]; a }",
+                "Expected next token to be RBracket",
+            ),
+            (
+                "fn() { if (y) { let z } z }",
+                "Expected next token to be Assign",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let err = parse(input).expect_err(input);
+            // Recovery within the block does not cascade into more errors
+            assert_eq!(err.errors().len(), 1, "{}: {}", input, err);
+            assert!(
+                err.errors()[0].to_string().contains(expected),
+                "{} should contain {}",
+                err,
+                expected
+            );
+        }
+
+        let err = parse("if (x) { let a 1; let b 2; }").unwrap_err();
+        assert_eq!(err.errors().len(), 2, "{}", err);
+    }
+
+    #[test]
+    fn test_statement_separators() {
+        let cases = [
+            ("a; (b)", vec!["a", "b"]),
+            ("a\n(b)", vec!["a", "b"]),
+            ("a\n[1]", vec!["a", "[1]"]),
+            (
+                "let x = 1\nlet y = x\ny",
+                vec!["let x = 1;", "let y = x;", "y"],
+            ),
+            ("fn() { 1 }\n(2)", vec!["fn() { 1 }", "2"]),
+            // An operator at the end or start of a line continues the
+            // expression
+            ("1 +\n2", vec!["(1 + 2)"]),
+            ("x\n-1", vec!["(x - 1)"]),
+            ("a\n  == b", vec!["(a == b)"]),
+            ("f(\n1)", vec!["f(1)"]),
+            // Newlines within parentheses and brackets do not separate
+            ("(1\n+ 2)", vec!["(1 + 2)"]),
+            ("[1\n- 2, a\n[0]]", vec!["[(1 - 2), (a[0])]"]),
+            // ... except within a block, whose statements they separate
+            ("f(fn() { a\n(b) })", vec!["f(fn() { ab })"]),
+            ("f(fn() { a\n(b) }\n(c))", vec!["f(fn() { ab }(c))"]),
+        ];
+
+        for (input, expected) in cases {
+            match parse_with_newlines(input) {
+                Ok(ast::Node::Program(stmts)) => assert_eq!(
+                    stmts.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+                    expected,
+                    "input: {:?}",
+                    input
+                ),
+                other => panic!("expected program for {:?}, got {:?}", input, other),
+            }
+        }
+
+        // By default, newlines never end an expression
+        let cases = [
+            ("a\n(b)", vec!["a(b)"]),
+            ("a\n[1]", vec!["(a[1])"]),
+            ("x\n-1", vec!["(x - 1)"]),
+            ("fn(a, b) { a\n - b }", vec!["fn(a, b) { (a - b) }"]),
+            (
+                "let x = 1\nlet y = x\ny",
+                vec!["let x = 1;", "let y = x;", "y"],
+            ),
+        ];
+
+        for (input, expected) in cases {
+            match parse(input) {
+                Ok(ast::Node::Program(stmts)) => assert_eq!(
+                    stmts.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+                    expected,
+                    "input: {:?}",
+                    input
+                ),
+                other => panic!("expected program for {:?}, got {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_chained_comparison() {
+        let cases = [
+            ("a < b == c < d", "((a < b) == (c < d))"),
+            ("(a < b) == true", "((a < b) == true)"),
+            ("a < b + c", "(a < (b + c))"),
+        ];
+        check_parse_test_cases(&cases);
+
+        for input in ["1 < 2 < 3", "1 > 2 < 3", "a < b + 1 > c", "f(1 < 2 < 3)"] {
+            let err = parse(input).unwrap_err();
+            assert!(
+                err.to_string().contains("Comparisons cannot be chained"),
+                "{} should reject the chained comparison: {}",
+                input,
+                err
+            );
+        }
+
+        let err = parse("1 < 2 < 3").unwrap_err();
+        assert_eq!(err.errors()[0].position(), Some(token::Position::new(1, 7)));
+    }
+
+    /// Checks the pretty-printed output of parsing an input program string
+    /// against the expected multi-line source.
+    fn check_pretty_test_cases(cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            match parse(input) {
+                Ok(program) => assert_eq!(expected, &program.pretty(0)),
+                Err(e) => panic!("Parsing error: {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_pretty_print_nested_blocks() {
+        let cases = [
+            (
+                "let f = fn(x) { if (x > 1) { return x * 2; } else { x } };",
+                "let f = fn(x) {\n  if (x > 1) {\n    return x * 2;\n  } else {\n    x;\n  };\n};",
+            ),
+            (
+                "if (a) { fn() { b }() } else if (c) { d } else { e }",
+                "if (a) {\n  fn() {\n    b;\n  }();\n} else if (c) {\n  d;\n} else {\n  e;\n};",
+            ),
+            ("let noop = fn() {};", "let noop = fn() {};"),
+            (
+                "let f = fn(x) { switch (x) { 1 => fn() { 2 }, _ => 3 } };",
+                "let f = fn(x) {\n  switch (x) {\n    1 => fn() {\n      2;\n    },\n    _ => 3,\n  };\n};",
+            ),
+            ("switch (x) {}", "switch (x) {};"),
+            (
+                "let y = do { let z = 5; z * 2 };",
+                "let y = do {\n  let z = 5;\n  z * 2;\n};",
+            ),
+        ];
+        check_pretty_test_cases(&cases);
+    }
+
+    #[test]
+    fn test_pretty_print_precedence() {
+        let cases = [
+            ("a + b * c", "a + b * c;"),
+            ("(a + b) * c", "(a + b) * c;"),
+            ("a - (b - c)", "a - (b - c);"),
+            ("(a - b) - c", "a - b - c;"),
+            ("-(a + b)", "-(a + b);"),
+            ("(a + b)[0]", "(a + b)[0];"),
+            ("a ** (b ** c)", "a ** b ** c;"),
+            ("(a ** b) ** c", "(a ** b) ** c;"),
+            (
+                "let x = 1; return [x, {\"k\": x}];",
+                "let x = 1;\nreturn [x, {\"k\": x}];",
+            ),
+        ];
+        check_pretty_test_cases(&cases);
+    }
+
     #[test]
     fn test_parsing_hash_literals_string_keys() {
         let case = [(
@@ -926,4 +1951,48 @@ mod tests {
         )];
         check_parse_test_cases(&case);
     }
+
+    #[test]
+    fn test_trailing_commas() {
+        let cases = [
+            ("[1, 2, 3,]", "[1, 2, 3]"),
+            ("{\"a\": 1, \"b\": 2,}", "{\"a\": 1, \"b\": 2}"),
+            ("add(1, 2,)", "add(1, 2)"),
+            ("fn(x, y,) { x };", "fn(x, y) { x }"),
+        ];
+        check_parse_test_cases(&cases);
+
+        for input in ["[,]", "[1,,]", "add(,)", "fn(,) {}", "{,}"] {
+            assert!(parse(input).is_err(), "expected parse error: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_render_diagnostic() {
+        let cases = [
+            (
+                "let a = 1;\nlet b = (2 + 3;\nb",
+                "Encountered 1 error(s) while parsing:\n\
+                 Expected next token to be RParen, received Some(Semicolon)\n \
+                 --> 2:15\n  \
+                 |\n\
+                 2 | let b = (2 + 3;\n  \
+                 |               ^",
+            ),
+            (
+                "let f = fn(x) {\n  x + 1;\n",
+                "Encountered 1 error(s) while parsing:\n\
+                 Expected '}' to close the block, reached end of input\n \
+                 --> 3:1\n  \
+                 |\n\
+                 3 | \n  \
+                 | ^",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let err = parse(input).expect_err("input should fail to parse");
+            assert_eq!(err.render(input), expected, "input: {:?}", input);
+        }
+    }
 }
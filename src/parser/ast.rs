@@ -6,10 +6,10 @@ Defines the nodes that comprise the constructed AST from Monkey source code.
 
 use std::fmt;
 
-use crate::token;
+use crate::{lexer, symbol::Symbol, token, token::Position};
 
 /// Defines the nodes that comprise the constructed AST from Monkey source code.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Node {
     /// A program node, which contains a series of statements.
     Program(Vec<Statement>),
@@ -32,26 +32,45 @@ impl fmt::Display for Node {
 /// A statement doesn't produce a value, but rather performs an action or
 /// defines a variable.
 ///
-/// In Monkey, there are only three types of statements:
+/// In Monkey, there are only five types of statements:
 /// 1.  `let` statements, which define a variable with an identifier and an
 ///     expression.
-/// 2.  `return` statements, which return an expression.
-/// 3.  `expression` statements, which are expressions that don't return a value.
+/// 2.  assignment statements, which rebind an existing variable to the value
+///     of an expression.
+/// 3.  `return` statements, which return an expression.
+/// 4.  `break` and `continue` statements, which end a loop or its current
+///     iteration.
+/// 5.  `expression` statements, which are expressions that don't return a value.
 ///
 /// # Examples
 ///
 /// ```monkey
 /// let x = 5;  // let statement
+/// x = 6;      // assignment statement
 /// return x;   // return statement
+/// break;      // break statement
 /// x + 1;      // expression statement
 /// ```
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Statement {
     /// A let statement, which defines a variable with an identifier and an
     /// expression.
-    Let(String, Expression),
+    Let(Symbol, Expression),
+    /// A let statement destructuring a hash, which defines a variable for
+    /// each `(key, identifier)` pair bound to the value of the key in the hash
+    /// an expression evaluates to, and the position of the pattern.
+    LetHash(Vec<(Symbol, Symbol)>, Expression, Position),
+    /// An assignment statement, which rebinds an existing variable with an
+    /// identifier to the value of an expression, and the position of the
+    /// identifier.
+    Assign(Symbol, Expression, Position),
     /// A return statement, which returns an expression.
     Return(Expression),
+    /// A break statement, which ends the innermost enclosing loop.
+    Break,
+    /// A continue statement, which skips to the next iteration of the
+    /// innermost enclosing loop.
+    Continue,
     /// An expression statement, which is an expression that doesn't return a
     /// value.
     Expr(Expression),
@@ -61,31 +80,59 @@ impl fmt::Display for Statement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Statement::Let(id, expr) => write!(f, "let {} = {};", id, expr),
+            Statement::LetHash(bindings, expr, _) => {
+                write!(f, "let {} = {};", display_hash_pattern(bindings), expr)
+            }
+            Statement::Assign(id, expr, _) => write!(f, "{} = {};", id, expr),
             Statement::Return(expr) => write!(f, "return {};", expr),
+            Statement::Break => write!(f, "break;"),
+            Statement::Continue => write!(f, "continue;"),
             Statement::Expr(expr) => write!(f, "{}", expr),
         }
     }
 }
 
+/// Render the `(key, identifier)` pairs of a hash pattern, abbreviating a pair
+/// binding a key to an identifier of the same name to just the key.
+pub(crate) fn display_hash_pattern(bindings: &[(Symbol, Symbol)]) -> String {
+    let bindings = bindings
+        .iter()
+        .map(|(key, ident)| {
+            if key == ident {
+                key.to_string()
+            } else {
+                format!("{}: {}", key, ident)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("{{{}}}", bindings)
+}
+
 /// Represents the series of statements enclosed within an opening `{{` and a
 /// closing `}}`.
 pub type BlockStatement = Vec<Statement>;
 
 /// An expression is a value or a computation that produces a value.
+///
+/// Expressions whose evaluation can fail carry the [`Position`] of the token
+/// they were parsed from, so that errors can point back to the source: the
+/// identifier, the operator, or the opening parenthesis or bracket of a call,
+/// index, or slice.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Expression {
     /// An identifier expression, which represents a variable.
-    Identifier(String),
+    Identifier(Symbol, Position),
 
     /// A literal expression, e.g. an integer, boolean, string, array, or hash.
     Lit(Literal),
 
     /// A prefix parse function
-    Prefix(token::Token, Box<Expression>),
+    Prefix(token::Token, Box<Expression>, Position),
 
     /// An infix parse function, which takes another expression (the "left
     /// side") as an argument
-    Infix(token::Token, Box<Expression>, Box<Expression>),
+    Infix(token::Token, Box<Expression>, Box<Expression>, Position),
 
     /// An if expression, where the produced value is the last evaluated line.
     /// An if expression can be defined by the following grammar:
@@ -105,13 +152,19 @@ pub enum Expression {
     /// ```ebnf
     /// (<parameter one>, <parameter two>, <parameter three>, ...)
     /// ```
-    Fn(Vec<String>, BlockStatement),
+    /// Parameters may be given a default value, e.g. `y = 10`, after all of
+    /// the required parameters, and the last parameter may be a rest
+    /// parameter, e.g. `...rest`, collecting any extra arguments.
+    Fn(Vec<Parameter>, BlockStatement),
 
     /// A function call expression, which can be represented abstractly as:
     /// ```ebnf
-    /// <expression>(<comma-separated expressions>)
+    /// <expression>(<comma-separated arguments>)
     /// ```
-    Call(Box<Expression>, Vec<Expression>),
+    /// where each argument is either an expression, or a keyword argument
+    /// `<identifier>: <expression>` binding a parameter by name. Keyword
+    /// arguments must follow all positional arguments.
+    Call(Box<Expression>, Vec<Argument>, Position),
 
     /// Index expression. Abstractly, an index expression can be represented as
     /// follows:
@@ -121,15 +174,61 @@ pub enum Expression {
     ///
     /// The left expression is the object being accessed, and the right index
     /// expression is an expression that semantic must produce an integer.
-    Index(Box<Expression>, Box<Expression>),
+    Index(Box<Expression>, Box<Expression>, Position),
+
+    /// Slice expression. Abstractly, a slice expression can be represented as
+    /// follows:
+    /// ```ebnf
+    /// <expression>\[<start expression>?:<end expression>?\]
+    /// ```
+    ///
+    /// The left expression is the string or array being sliced, and the
+    /// optional start and end expressions bound the half-open range of the
+    /// slice. An omitted start or end extends the slice to the respective end
+    /// of the sliced object.
+    Slice(
+        Box<Expression>,
+        Option<Box<Expression>>,
+        Option<Box<Expression>>,
+        Position,
+    ),
+
+    /// A switch expression, which evaluates to the value of the first arm
+    /// whose pattern matches the subject. Abstractly, a switch expression can
+    /// be represented as follows:
+    /// ```ebnf
+    /// switch (<subject>) { <pattern> => <expression>, ... }
+    /// ```
+    /// where each pattern is either an expression, matching a subject equal to
+    /// its value, or the wildcard `_`, matching any subject. The position is
+    /// that of the subject.
+    Switch(Box<Expression>, Vec<(Pattern, Expression)>, Position),
+
+    /// A do expression, which evaluates the statements of its block in a new
+    /// scope to the value of the last one. Abstractly, a do expression can be
+    /// represented as follows:
+    /// ```ebnf
+    /// do { <block statement> }
+    /// ```
+    Do(Vec<Statement>),
+
+    /// A for-in expression, which evaluates its block once for each element
+    /// of an array, or each `[key, value]` entry of a hash, bound to an
+    /// identifier in a new scope, and evaluates to `null`. Abstractly, a
+    /// for-in expression can be represented as follows:
+    /// ```ebnf
+    /// for (<identifier> in <expression>) { <block statement> }
+    /// ```
+    /// The position is that of the iterated expression.
+    For(Symbol, Box<Expression>, Vec<Statement>, Position),
 }
 
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Expression::Identifier(id) => write!(f, "{}", id),
-            Expression::Prefix(op, right) => write!(f, "({}{})", op, right),
-            Expression::Infix(op, left, right) => write!(f, "({} {} {})", left, op, right),
+            Expression::Identifier(id, _) => write!(f, "{}", id),
+            Expression::Prefix(op, right, _) => write!(f, "({}{})", op, right),
+            Expression::Infix(op, left, right, _) => write!(f, "({} {} {})", left, op, right),
             Expression::Lit(literal) => write!(f, "{}", literal),
             Expression::If(condition, consequence, alternative) => {
                 if let Some(alternative) = alternative {
@@ -153,14 +252,101 @@ impl fmt::Display for Expression {
                 write!(
                     f,
                     "fn({}) {{ {} }}",
-                    parameters.join(", "),
+                    display_parameters(parameters),
                     display_statements(body)
                 )
             }
-            Expression::Call(function_expr, arguments) => {
-                write!(f, "{}({})", function_expr, display_expressions(arguments))
+            Expression::Call(function_expr, arguments, _) => {
+                write!(f, "{}({})", function_expr, display_arguments(arguments))
+            }
+            Expression::Index(left, index, _) => write!(f, "({}[{}])", left, index),
+            Expression::Slice(left, start, end, _) => {
+                let start = start.as_ref().map_or(String::new(), |s| s.to_string());
+                let end = end.as_ref().map_or(String::new(), |e| e.to_string());
+                write!(f, "({}[{}:{}])", left, start, end)
+            }
+            Expression::Switch(subject, arms, _) => {
+                write!(f, "switch {} {{ {} }}", subject, display_arms(arms))
+            }
+            Expression::Do(body) => write!(f, "do {{ {} }}", display_statements(body)),
+            Expression::For(ident, iterable, body, _) => write!(
+                f,
+                "for {} in {} {{ {} }}",
+                ident,
+                iterable,
+                display_statements(body)
+            ),
+        }
+    }
+}
+
+/// A pattern of a switch expression arm.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Pattern {
+    /// A pattern matching a subject equal to the value of the expression, e.g.
+    /// `1`
+    Value(Expression),
+    /// The wildcard pattern `_`, matching any subject
+    Wildcard,
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Value(expr) => write!(f, "{}", expr),
+            Pattern::Wildcard => write!(f, "_"),
+        }
+    }
+}
+
+/// A parameter of a function literal.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Parameter {
+    /// A parameter that must be passed an argument, e.g. `x`
+    Required(Symbol),
+    /// A parameter with a default value, evaluated when the argument is
+    /// omitted, e.g. `y = 10`
+    Default(Symbol, Expression),
+    /// A rest parameter, bound to an array of the arguments following the
+    /// other parameters, e.g. `...rest`
+    Rest(Symbol),
+}
+
+impl Parameter {
+    /// Returns the identifier the parameter binds.
+    pub fn name(&self) -> Symbol {
+        match self {
+            Parameter::Required(name) | Parameter::Default(name, _) | Parameter::Rest(name) => {
+                *name
             }
-            Expression::Index(left, index) => write!(f, "({}[{}])", left, index),
+        }
+    }
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Parameter::Required(name) => write!(f, "{}", name),
+            Parameter::Default(name, default) => write!(f, "{} = {}", name, default),
+            Parameter::Rest(name) => write!(f, "...{}", name),
+        }
+    }
+}
+
+/// An argument of a function call expression.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Argument {
+    /// An argument bound to a parameter by position, e.g. `1`
+    Positional(Expression),
+    /// An argument bound to a parameter by name, e.g. `x: 1`
+    Keyword(Symbol, Expression),
+}
+
+impl fmt::Display for Argument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Argument::Positional(expr) => write!(f, "{}", expr),
+            Argument::Keyword(name, expr) => write!(f, "{}: {}", name, expr),
         }
     }
 }
@@ -169,9 +355,11 @@ impl fmt::Display for Expression {
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Literal {
     /// An integer literal, e.g. `5;`
-    Integer(i32),
+    Integer(i64),
     /// A Boolean literal, e.g. `true` or `false`
     Boolean(bool),
+    /// The null literal `null`
+    Null,
     /// A string literal, e.g. `\"Hello world!\"`
     String(String),
     /// An array literal, e.g. `\[1, 2, 3 + 3, fn(x) { x }, add(2, 2)\]`
@@ -185,7 +373,8 @@ impl fmt::Display for Literal {
         match self {
             Literal::Integer(int) => write!(f, "{}", int),
             Literal::Boolean(bool) => write!(f, "{}", bool),
-            Literal::String(str) => write!(f, "\"{}\"", str),
+            Literal::Null => write!(f, "null"),
+            Literal::String(str) => write!(f, "{}", lexer::escape(str)),
             Literal::Array(expressions) => write!(f, "[{}]", display_expressions(expressions)),
             Literal::Hash(entries) => {
                 let hash = entries
@@ -209,6 +398,33 @@ pub fn display_statements(stmts: &[Statement]) -> String {
         .join("")
 }
 
+/// Format function parameters into a comma-separated string representation.
+pub fn display_parameters(parameters: &[Parameter]) -> String {
+    parameters
+        .iter()
+        .map(|param| param.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Format call arguments into a comma-separated string representation.
+pub fn display_arguments(arguments: &[Argument]) -> String {
+    arguments
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Format switch expression arms into a comma-separated string
+/// representation.
+pub fn display_arms(arms: &[(Pattern, Expression)]) -> String {
+    arms.iter()
+        .map(|(pattern, expr)| format!("{} => {}", pattern, expr))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
 /// Format expressions array into a comma-separated string representation.
 pub fn display_expressions(expressions: &[Expression]) -> String {
     expressions
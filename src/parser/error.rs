@@ -2,17 +2,28 @@
 # Error
 
 Defines the `ParserError` type, which is used to represent errors that occur
-during parsing.
+during parsing, and renders them as diagnostics pointing into the source code.
 */
 use std::fmt;
 
+use crate::token::Position;
+
 /// An error encountered while performing parsing.
 #[derive(Debug, Clone)]
-pub struct ParserError(String);
+pub struct ParserError {
+    /// The human-readable message to display.
+    msg: String,
+    /// The position in the source code of the token the error was raised at,
+    /// if known.
+    position: Option<Position>,
+    /// The individual errors collected into this error, e.g., every error of
+    /// a program. Empty for a single error.
+    errors: Vec<ParserError>,
+}
 
 impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.msg)
     }
 }
 
@@ -21,6 +32,89 @@ impl std::error::Error for ParserError {}
 impl ParserError {
     /// Construct a new parser error with the given message to display.
     pub fn new(msg: String) -> Self {
-        ParserError(msg)
+        ParserError {
+            msg,
+            position: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Construct a parser error collecting every error encountered while
+    /// parsing a program.
+    pub fn collect(errors: Vec<ParserError>) -> Self {
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        ParserError {
+            msg: format!(
+                "Encountered {} error(s) while parsing:\n{}",
+                errors.len(),
+                messages.join("\n")
+            ),
+            position: None,
+            errors,
+        }
+    }
+
+    /// Attach the position of the token the error was raised at, unless the
+    /// error already has a position.
+    pub fn at(mut self, position: Position) -> Self {
+        self.position.get_or_insert(position);
+        self
+    }
+
+    /// Returns the position in the source code of the token the error was
+    /// raised at, if known.
+    pub fn position(&self) -> Option<Position> {
+        self.position
     }
+
+    /// Returns the individual errors collected into this error, which is empty
+    /// for a single error.
+    pub fn errors(&self) -> &[ParserError] {
+        &self.errors
+    }
+
+    /// Render the error as a diagnostic against the source code it was parsed
+    /// from, showing the offending line with a caret under the position of
+    /// each error. Errors without a position are rendered as their message.
+    pub fn render(&self, source: &str) -> String {
+        if self.errors.is_empty() {
+            return match self.position {
+                Some(position) => render_diagnostic(source, position, &self.msg),
+                None => self.msg.clone(),
+            };
+        }
+
+        let diagnostics: Vec<String> = self.errors.iter().map(|e| e.render(source)).collect();
+        format!(
+            "Encountered {} error(s) while parsing:\n{}",
+            self.errors.len(),
+            diagnostics.join("\n")
+        )
+    }
+}
+
+/// Render a diagnostic for the message at the given position in the source
+/// code, in the style of `rustc`:
+///
+/// ```text
+/// Expected next token to be RParen, received Some(LBrace)
+///  --> 1:7
+///   |
+/// 1 | if (x { 1 }
+///   |       ^
+/// ```
+pub fn render_diagnostic(source: &str, position: Position, msg: &str) -> String {
+    let line = source.lines().nth(position.line - 1).unwrap_or("");
+    let gutter = " ".repeat(position.line.to_string().len());
+    // Keep tabs in the caret's indentation so that it lines up with the line
+    let indent: String = line
+        .chars()
+        .take(position.column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    format!(
+        "{msg}\n{gutter}--> {position}\n{gutter} |\n{} | {line}\n{gutter} | {indent}^",
+        position.line
+    )
 }
@@ -7,7 +7,7 @@ use crate::token;
 
 /// Defines the precedences of the Monkey programming language.
 #[derive(Debug, PartialEq, PartialOrd, Eq)]
-pub enum Precdence {
+pub enum Precedence {
     /// The lowest value precedence
     Lowest,
     /// Equality comparison operator `==`
@@ -18,6 +18,8 @@ pub enum Precdence {
     Sum,
     /// Multiplication operator `*`
     Product,
+    /// Exponentiation operator `**`
+    Power,
     /// Prefix operators, e.g., `-X` or `!X`
     Prefix,
     /// Function calls, e.g., `myFunction(X)`
@@ -26,15 +28,20 @@ pub enum Precdence {
     Index,
 }
 
+/// Former, misspelled name of [`Precedence`].
+#[deprecated(note = "renamed to `Precedence`")]
+pub type Precdence = Precedence;
+
 /// Returns the precedence of a given [`token::Token`] value.
-pub fn token_precedence(token: &token::Token) -> Precdence {
+pub fn token_precedence(token: &token::Token) -> Precedence {
     match token {
-        token::Token::Eq | token::Token::NotEq => Precdence::Equals,
-        token::Token::Lt | token::Token::Gt => Precdence::LessGreater,
-        token::Token::Plus | token::Token::Minus => Precdence::Sum,
-        token::Token::Slash | token::Token::Asterisk => Precdence::Product,
-        token::Token::LParen => Precdence::Call,
-        token::Token::LBracket => Precdence::Index,
-        _ => Precdence::Lowest,
+        token::Token::Eq | token::Token::NotEq => Precedence::Equals,
+        token::Token::Lt | token::Token::Gt => Precedence::LessGreater,
+        token::Token::Plus | token::Token::Minus => Precedence::Sum,
+        token::Token::Slash | token::Token::Asterisk => Precedence::Product,
+        token::Token::Power => Precedence::Power,
+        token::Token::LParen => Precedence::Call,
+        token::Token::LBracket => Precedence::Index,
+        _ => Precedence::Lowest,
     }
 }
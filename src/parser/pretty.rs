@@ -0,0 +1,252 @@
+/*!
+# Pretty Printer
+
+Renders AST nodes as multi-line, indented Monkey source code. Unlike the
+compact `Display` output, which fully parenthesizes expressions on a single
+line, the pretty-printed form only adds the parentheses required by operator
+precedence and places each statement on its own line.
+*/
+use super::ast::{
+    display_hash_pattern, Argument, Expression, Literal, Node, Parameter, Pattern, Statement,
+};
+use super::precedence;
+use crate::token::Token;
+
+/// Number of spaces per indentation level.
+const INDENT_WIDTH: usize = 2;
+
+/// Returns the leading whitespace for the given indentation level.
+fn indentation(indent: usize) -> String {
+    " ".repeat(indent * INDENT_WIDTH)
+}
+
+impl Node {
+    /// Render the node as indented source code, starting at the given
+    /// indentation level.
+    pub fn pretty(&self, indent: usize) -> String {
+        match self {
+            Node::Program(stmts) => pretty_statements(stmts, indent),
+            Node::Stmt(stmt) => stmt.pretty(indent),
+            Node::Expr(expr) => format!("{}{}", indentation(indent), expr.pretty(indent)),
+        }
+    }
+}
+
+impl Statement {
+    /// Render the statement as indented source code on its own line(s),
+    /// starting at the given indentation level.
+    pub fn pretty(&self, indent: usize) -> String {
+        let stmt = match self {
+            Statement::Let(id, expr) => format!("let {} = {};", id, expr.pretty(indent)),
+            Statement::LetHash(bindings, expr, _) => format!(
+                "let {} = {};",
+                display_hash_pattern(bindings),
+                expr.pretty(indent)
+            ),
+            Statement::Assign(id, expr, _) => format!("{} = {};", id, expr.pretty(indent)),
+            Statement::Return(expr) => format!("return {};", expr.pretty(indent)),
+            Statement::Break => "break;".to_string(),
+            Statement::Continue => "continue;".to_string(),
+            Statement::Expr(expr) => format!("{};", expr.pretty(indent)),
+        };
+        format!("{}{}", indentation(indent), stmt)
+    }
+}
+
+impl Expression {
+    /// Render the expression as source code. The expression itself is not
+    /// indented, but any nested blocks are indented one level past the given
+    /// indentation level.
+    pub fn pretty(&self, indent: usize) -> String {
+        match self {
+            Expression::Identifier(id, _) => id.to_string(),
+            Expression::Lit(literal) => pretty_literal(literal, indent),
+            Expression::Prefix(op, right, _) => {
+                format!("{}{}", op, pretty_operand(right, indent))
+            }
+            Expression::Infix(op, left, right, _) => {
+                let op_precedence = precedence::token_precedence(op);
+                // Infix operators are left-associative, except for `**`, so
+                // an operand of equal precedence on the opposite side of the
+                // associativity must also be parenthesized.
+                let right_associative = *op == Token::Power;
+                let left = match **left {
+                    Expression::Infix(ref left_op, ..)
+                        if precedence::token_precedence(left_op) < op_precedence
+                            || (right_associative
+                                && precedence::token_precedence(left_op) == op_precedence) =>
+                    {
+                        format!("({})", left.pretty(indent))
+                    }
+                    _ => left.pretty(indent),
+                };
+                let right = match **right {
+                    Expression::Infix(ref right_op, ..)
+                        if precedence::token_precedence(right_op) < op_precedence
+                            || (!right_associative
+                                && precedence::token_precedence(right_op) == op_precedence) =>
+                    {
+                        format!("({})", right.pretty(indent))
+                    }
+                    _ => right.pretty(indent),
+                };
+                format!("{} {} {}", left, op, right)
+            }
+            Expression::If(condition, consequence, alternative) => {
+                let mut result = format!(
+                    "if ({}) {}",
+                    condition.pretty(indent),
+                    pretty_block(consequence, indent)
+                );
+                match alternative.as_deref() {
+                    // Render a nested if-expression alternative as an
+                    // `else if` chain.
+                    Some([Statement::Expr(nested @ Expression::If(..))]) => {
+                        result += &format!(" else {}", nested.pretty(indent));
+                    }
+                    Some(alternative) => {
+                        result += &format!(" else {}", pretty_block(alternative, indent));
+                    }
+                    None => {}
+                }
+                result
+            }
+            Expression::Fn(parameters, body) => {
+                format!(
+                    "fn({}) {}",
+                    pretty_parameters(parameters, indent),
+                    pretty_block(body, indent)
+                )
+            }
+            Expression::Call(function_expr, arguments, _) => format!(
+                "{}({})",
+                pretty_operand(function_expr, indent),
+                pretty_arguments(arguments, indent)
+            ),
+            Expression::Index(left, index, _) => {
+                format!("{}[{}]", pretty_operand(left, indent), index.pretty(indent))
+            }
+            Expression::Slice(left, start, end, _) => {
+                let start = start.as_ref().map_or(String::new(), |s| s.pretty(indent));
+                let end = end.as_ref().map_or(String::new(), |e| e.pretty(indent));
+                format!("{}[{}:{}]", pretty_operand(left, indent), start, end)
+            }
+            Expression::Switch(subject, arms, _) => {
+                if arms.is_empty() {
+                    return format!("switch ({}) {{}}", subject.pretty(indent));
+                }
+                let arms = arms
+                    .iter()
+                    .map(|(pattern, expr)| {
+                        let pattern = match pattern {
+                            Pattern::Value(value) => value.pretty(indent + 1),
+                            Pattern::Wildcard => pattern.to_string(),
+                        };
+                        format!(
+                            "{}{} => {},",
+                            indentation(indent + 1),
+                            pattern,
+                            expr.pretty(indent + 1)
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                format!(
+                    "switch ({}) {{\n{}\n{}}}",
+                    subject.pretty(indent),
+                    arms,
+                    indentation(indent)
+                )
+            }
+            Expression::Do(body) => format!("do {}", pretty_block(body, indent)),
+            Expression::For(ident, iterable, body, _) => format!(
+                "for ({} in {}) {}",
+                ident,
+                iterable.pretty(indent),
+                pretty_block(body, indent)
+            ),
+        }
+    }
+}
+
+/// Render an operand of a prefix, call, or index expression, parenthesizing
+/// operator expressions that would otherwise bind incorrectly.
+fn pretty_operand(expr: &Expression, indent: usize) -> String {
+    match expr {
+        Expression::Infix(..) | Expression::Prefix(..) => format!("({})", expr.pretty(indent)),
+        _ => expr.pretty(indent),
+    }
+}
+
+/// Render a literal expression.
+fn pretty_literal(literal: &Literal, indent: usize) -> String {
+    match literal {
+        Literal::Array(expressions) => format!("[{}]", pretty_expressions(expressions, indent)),
+        Literal::Hash(entries) => {
+            let hash = entries
+                .iter()
+                .map(|(k, v)| format!("{}: {}", k.pretty(indent), v.pretty(indent)))
+                .collect::<Vec<String>>()
+                .join(", ");
+            format!("{{{}}}", hash)
+        }
+        _ => literal.to_string(),
+    }
+}
+
+/// Render a block statement with each statement on its own line, indented one
+/// level past the given indentation level.
+fn pretty_block(block: &[Statement], indent: usize) -> String {
+    if block.is_empty() {
+        return "{}".to_string();
+    }
+    format!(
+        "{{\n{}\n{}}}",
+        pretty_statements(block, indent + 1),
+        indentation(indent)
+    )
+}
+
+/// Render statements at the given indentation level, delimited by newlines.
+fn pretty_statements(stmts: &[Statement], indent: usize) -> String {
+    stmts
+        .iter()
+        .map(|stmt| stmt.pretty(indent))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Render function parameters into a comma-separated list.
+fn pretty_parameters(parameters: &[Parameter], indent: usize) -> String {
+    parameters
+        .iter()
+        .map(|param| match param {
+            Parameter::Default(name, default) => {
+                format!("{} = {}", name, default.pretty(indent))
+            }
+            _ => param.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Render call arguments into a comma-separated list.
+fn pretty_arguments(arguments: &[Argument], indent: usize) -> String {
+    arguments
+        .iter()
+        .map(|arg| match arg {
+            Argument::Positional(expr) => expr.pretty(indent),
+            Argument::Keyword(name, expr) => format!("{}: {}", name, expr.pretty(indent)),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Render expressions into a comma-separated list.
+fn pretty_expressions(expressions: &[Expression], indent: usize) -> String {
+    expressions
+        .iter()
+        .map(|expr| expr.pretty(indent))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
@@ -2,21 +2,60 @@
 # REPL
 
 Defines a Read-Eval-Print-Loop (REPL) for the Monkey programming language.
+
+Lines starting with `:` are interpreted as meta-commands rather than Monkey
+code, e.g., `:help` lists the available commands.
+
+Sessions can be saved with `:save` as a script of `let` statements and restored
+with `:load-session`. Each binding is rendered back to source, so values that
+have no source representation, e.g., builtins, are skipped. Functions are
+restored as closures over the session's environment rather than the
+environment they were originally defined in, so closures capturing local
+bindings are skipped too.
 */
 use rustyline::error::ReadlineError;
 use rustyline::{DefaultEditor, Result};
 use std::cell::RefCell;
 use std::fs;
+use std::io;
+use std::path::Path;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use crate::eval;
+use crate::eval::context::Context;
 use crate::eval::environment::Env;
+use crate::eval::object::Object;
+use crate::lexer;
 use crate::parser;
+use crate::token::Token;
+
+/// Help text listing the available REPL meta-commands.
+const HELP: &str = "\
+:help         Show this help message
+:quit         Exit the REPL
+:env          Print the bindings of the session, one `name = value` per line
+:clear        Discard all bindings of the session
+:type <expr>  Evaluate <expr> and print the name of its type, e.g., INTEGER
+:time <expr>  Evaluate <expr>, printing its value and how long evaluation took
+:load <path>  Evaluate the Monkey source file at <path> into the session
+:save <path>  Save the bindings of the session to <path> as `let` statements
+:load-session <path>
+              Replace the session with the bindings saved to <path>";
+
+/// The outcome of running a REPL meta-command.
+enum Action {
+    /// Continue reading input.
+    Continue,
+    /// Exit the REPL.
+    Quit,
+}
 
 /// Runs a simple Read-Eval-Print-Loop (REPL) for the user to run Monkey code.
 pub fn start() -> Result<()> {
     let mut rl = DefaultEditor::new()?;
-    let env: Env = Rc::new(RefCell::new(Default::default()));
+    let mut env: Env = Rc::new(RefCell::new(Default::default()));
+    let mut ctx = Context::default();
     let history_path = "/tmp/.monkey-history.txt";
 
     match rl.load_history(history_path) {
@@ -43,7 +82,6 @@ pub fn start() -> Result<()> {
 
     loop {
         let readline = rl.readline(">> ");
-        let mut input = String::new();
 
         match readline {
             Ok(mut line) => {
@@ -54,49 +92,36 @@ pub fn start() -> Result<()> {
                     continue;
                 }
 
-                loop {
-                    if line.as_bytes().ends_with(b"\\") {
-                        // Strip final backslash and add to current input
-                        line.pop();
-                        input += &line;
-
-                        // Re-prompt for additional lines
-                        match rl.readline(".. ") {
-                            Ok(next) => {
-                                line = next;
-                                while line.ends_with(' ') {
-                                    line.pop();
-                                }
-                            }
-                            Err(ReadlineError::Eof | ReadlineError::Interrupted) => {
-                                println!("Exiting...");
-                                rl.save_history(history_path)?;
-                                return Ok(());
-                            }
-                            Err(err) => {
-                                println!("Error: {:?}", err);
-                                return Err(err);
-                            }
-                        }
-                    } else {
-                        // Final line
-                        while line.ends_with(' ') {
-                            line.pop();
+                // Meta-commands only apply to lines starting with `:`, so
+                // colons within code, e.g., hash literals, are unaffected
+                if line.starts_with(':') {
+                    rl.add_history_entry(&line)?;
+                    match run_command(&line, &mut env, &mut ctx) {
+                        Action::Continue => continue,
+                        Action::Quit => {
+                            println!("Exiting...");
+                            rl.save_history(history_path)?;
+                            break;
                         }
-                        input += &line;
-                        break;
                     }
                 }
 
+                let input = match read_input(line, || rl.readline(".. ")) {
+                    Ok(input) => input,
+                    Err(ReadlineError::Eof | ReadlineError::Interrupted) => {
+                        println!("Exiting...");
+                        rl.save_history(history_path)?;
+                        return Ok(());
+                    }
+                    Err(err) => {
+                        println!("Error: {:?}", err);
+                        return Err(err);
+                    }
+                };
+
                 rl.add_history_entry(&input)?;
 
-                match parser::parse(&input) {
-                    Ok(program) => match eval::eval(program, &Rc::clone(&env)) {
-                        Ok(evaluated) => println!("{}", evaluated),
-                        Err(e) => eprintln!("{}", e),
-                    },
-                    Err(e) => eprintln!("{}", e),
-                }
+                eval_input(&input, &env, &mut ctx);
             }
             Err(ReadlineError::Eof | ReadlineError::Interrupted) => {
                 println!("Exiting...");
@@ -112,3 +137,318 @@ pub fn start() -> Result<()> {
 
     Ok(())
 }
+
+/// Read a complete input program starting from the given first line, reading
+/// continuation lines with `next_line` while the input is incomplete. Input is
+/// incomplete if a line ends with a backslash, which is stripped, or if the
+/// input has unclosed parentheses, braces, or brackets.
+fn read_input(mut line: String, mut next_line: impl FnMut() -> Result<String>) -> Result<String> {
+    let mut input = String::new();
+
+    loop {
+        while line.ends_with(' ') {
+            line.pop();
+        }
+
+        if line.ends_with('\\') {
+            // Strip final backslash and add to current input
+            line.pop();
+            input += &line;
+        } else {
+            input += &line;
+            if !is_incomplete(&input) {
+                return Ok(input);
+            }
+            input.push('\n');
+        }
+
+        // Re-prompt for additional lines
+        line = next_line()?;
+    }
+}
+
+/// Returns whether the input has more opening parentheses, braces, or brackets
+/// than closing ones, i.e., whether more lines are needed to complete it.
+fn is_incomplete(input: &str) -> bool {
+    let mut depth: i64 = 0;
+    for token in lexer::tokenize(input) {
+        match token {
+            Token::LParen | Token::LBrace | Token::LBracket => depth += 1,
+            Token::RParen | Token::RBrace | Token::RBracket => depth -= 1,
+            _ => {}
+        }
+    }
+    depth > 0
+}
+
+/// Parse and evaluate the input within the session's environment, printing the
+/// result or error.
+fn eval_input(input: &str, env: &Env, ctx: &mut Context) {
+    match parser::parse_with_newlines(input) {
+        Ok(program) => match eval::eval_with_context(program, &Rc::clone(env), ctx) {
+            Ok(evaluated) => println!("{}", evaluated),
+            Err(e) => eprintln!("{}", e),
+        },
+        Err(e) => eprintln!("{}", e.render(input)),
+    }
+}
+
+/// Run the meta-command on the given line, which starts with `:`.
+fn run_command(line: &str, env: &mut Env, ctx: &mut Context) -> Action {
+    let (command, arg) = split_command(line);
+
+    match command {
+        ":quit" => return Action::Quit,
+        ":help" => println!("{}", HELP),
+        ":env" => {
+            for (name, val) in env.borrow().bindings() {
+                println!("{} = {}", name, val);
+            }
+        }
+        ":clear" => *env = Rc::new(RefCell::new(Default::default())),
+        ":type" | ":time" if arg.is_empty() => eprintln!("usage: {} <expr>", command),
+        ":load" | ":save" | ":load-session" if arg.is_empty() => {
+            eprintln!("usage: {} <path>", command)
+        }
+        ":type" => match type_of(arg, env, ctx) {
+            Ok(type_name) => println!("{}", type_name),
+            Err(e) => eprintln!("{}", e),
+        },
+        ":time" => match time_input(arg, env, ctx) {
+            Ok((evaluated, elapsed)) => {
+                println!("{}", evaluated);
+                println!("evaluated in {:?} (excluding parsing)", elapsed);
+            }
+            Err(e) => eprintln!("{}", e),
+        },
+        ":load" => match load_file(arg, env, ctx) {
+            Ok(evaluated) => println!("{}", evaluated),
+            Err(e) => eprintln!("{}", e),
+        },
+        ":save" => match save_session(env, arg) {
+            Ok(skipped) => {
+                for name in skipped {
+                    eprintln!("skipped `{}`: value cannot be saved as source", name);
+                }
+            }
+            Err(e) => eprintln!("could not write `{}`: {}", arg, e),
+        },
+        ":load-session" => {
+            let session: Env = Rc::new(RefCell::new(Default::default()));
+            match load_file(arg, &session, ctx) {
+                Ok(_) => *env = session,
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        _ => eprintln!("unknown command: {} (see :help)", command),
+    }
+
+    Action::Continue
+}
+
+/// Split a meta-command line into the command, e.g., `:load`, and its argument
+/// with surrounding whitespace trimmed, which is empty if there is none.
+fn split_command(line: &str) -> (&str, &str) {
+    match line.split_once(char::is_whitespace) {
+        Some((command, arg)) => (command, arg.trim()),
+        None => (line, ""),
+    }
+}
+
+/// Parse and evaluate the input within the environment, returning its value
+/// and the wall-clock time taken to evaluate it, excluding parsing. The input
+/// is parsed as typed input is.
+fn time_input(
+    input: &str,
+    env: &Env,
+    ctx: &mut Context,
+) -> std::result::Result<(Rc<Object>, Duration), String> {
+    let program = parser::parse_with_newlines(input).map_err(|e| e.render(input))?;
+    let start = Instant::now();
+    let evaluated =
+        eval::eval_with_context(program, &Rc::clone(env), ctx).map_err(|e| e.to_string())?;
+    Ok((evaluated, start.elapsed()))
+}
+
+/// Parse and evaluate the input within the environment, returning the name of
+/// the type of its value. The input is parsed as typed input is.
+fn type_of(input: &str, env: &Env, ctx: &mut Context) -> std::result::Result<&'static str, String> {
+    let program = parser::parse_with_newlines(input).map_err(|e| e.render(input))?;
+    let evaluated =
+        eval::eval_with_context(program, &Rc::clone(env), ctx).map_err(|e| e.to_string())?;
+    Ok(evaluated.type_name())
+}
+
+/// Evaluate the Monkey source file at the given path into the environment,
+/// returning the value of its last statement. Imports within the file are
+/// resolved relative to it. The file is parsed as it would be when run from
+/// the command line, rather than as typed input, so it has the same meaning
+/// in and out of the REPL.
+fn load_file(path: &str, env: &Env, ctx: &mut Context) -> std::result::Result<Rc<Object>, String> {
+    let input =
+        fs::read_to_string(path).map_err(|e| format!("could not read `{}`: {}", path, e))?;
+    let program = parser::parse(&input).map_err(|e| e.render(&input))?;
+
+    let path = Path::new(path);
+    ctx.push_file(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+    let evaluated = eval::eval_with_context(program, &Rc::clone(env), ctx);
+    ctx.pop_file();
+
+    evaluated.map_err(|e| e.to_string())
+}
+
+/// Save the bindings of the environment to the given path as a Monkey script of
+/// `let` statements, returning the names of the bindings that were skipped
+/// because their values cannot be rendered as source.
+fn save_session(env: &Env, path: &str) -> io::Result<Vec<String>> {
+    let mut script = String::new();
+    let mut skipped = Vec::new();
+
+    for (name, val) in env.borrow().bindings() {
+        match val.to_source() {
+            Some(source) => script += &format!("let {} = {};\n", name, source),
+            None => skipped.push(name),
+        }
+    }
+
+    fs::write(path, script)?;
+    Ok(skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a `next_line` callback that yields the given lines in order.
+    fn lines<'a>(lines: &'a [&'a str]) -> impl FnMut() -> Result<String> + 'a {
+        let mut lines = lines.iter();
+        move || {
+            lines
+                .next()
+                .map(|line| line.to_string())
+                .ok_or(ReadlineError::Eof)
+        }
+    }
+
+    #[test]
+    fn test_read_input_continues_unclosed_brackets() {
+        let input = read_input(
+            "let max = fn(a, b) {".to_string(),
+            lines(&[
+                "  if (a > b) {",
+                "    a",
+                "  } else {",
+                "    b",
+                "  }",
+                "};",
+            ]),
+        )
+        .unwrap();
+
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let program = parser::parse(&format!("{} max(3, [1, 7][1])", input)).unwrap();
+        let evaluated = eval::eval(program, &env).unwrap();
+        assert_eq!(*evaluated, Object::Integer(7));
+    }
+
+    #[test]
+    fn test_read_input_backslash_continuation() {
+        let input = read_input("let x = 1 + \\".to_string(), lines(&["2;"])).unwrap();
+        assert_eq!(input, "let x = 1 + 2;");
+
+        let input = read_input("1 + 2".to_string(), lines(&[])).unwrap();
+        assert_eq!(input, "1 + 2");
+
+        let result = read_input("fn(x) {".to_string(), lines(&[]));
+        assert!(matches!(result, Err(ReadlineError::Eof)));
+    }
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(split_command(":quit"), (":quit", ""));
+        assert_eq!(split_command(":load  lib.monkey "), (":load", "lib.monkey"));
+        assert_eq!(split_command(":time fib(10) + 1"), (":time", "fib(10) + 1"));
+        assert_eq!(split_command(":type\t[1]"), (":type", "[1]"));
+        assert_eq!(split_command(":time "), (":time", ""));
+    }
+
+    #[test]
+    fn test_time_input() {
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let mut ctx = Context::default();
+
+        let (evaluated, _) = time_input("let x = 20; x * 2 + 2", &env, &mut ctx).unwrap();
+        assert_eq!(*evaluated, Object::Integer(42));
+        // Bindings are made in the session
+        let (evaluated, _) = time_input("x", &env, &mut ctx).unwrap();
+        assert_eq!(*evaluated, Object::Integer(20));
+
+        assert_eq!(
+            time_input("y", &env, &mut ctx).unwrap_err(),
+            "identifier not found: y"
+        );
+        assert!(time_input("let = 1", &env, &mut ctx).is_err());
+    }
+
+    #[test]
+    fn test_type_of() {
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let mut ctx = Context::default();
+
+        let cases = [
+            ("1 + 1", "INTEGER"),
+            ("true", "BOOLEAN"),
+            (r#""a""#, "STRING"),
+            ("if (false) { 1 }", "NULL"),
+            ("fn(x) { x }", "FUNCTION"),
+            ("len", "BUILTIN"),
+            ("[1]", "ARRAY"),
+            ("{1: 2}", "HASH"),
+            ("let x = [1];", "ARRAY"),
+            ("x[0]", "INTEGER"),
+            // Newlines separate statements as in typed input
+            ("let f = fn(x) { x }\n[1]", "ARRAY"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(type_of(input, &env, &mut ctx), Ok(expected), "{}", input);
+        }
+
+        assert_eq!(
+            type_of("1 + true", &env, &mut ctx),
+            Err("unknown operator: 1 + true".to_string())
+        );
+        let err = type_of("let = 1", &env, &mut ctx).unwrap_err();
+        assert!(err.contains("Expected identifier after 'let'"), "{}", err);
+    }
+
+    #[test]
+    fn test_save_and_load_session() {
+        let path =
+            std::env::temp_dir().join(format!("monkey-rs-session-{}.monkey", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut ctx = Context::default();
+
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let program = parser::parse(
+            r#"let x = 5; let add = fn(a, b) { a + b }; let l = len; let s = "say \"hi\"\n";"#,
+        )
+        .unwrap();
+        eval::eval_with_context(program, &env, &mut ctx).unwrap();
+
+        let skipped = save_session(&env, path).unwrap();
+        assert_eq!(skipped, ["l"]);
+
+        let restored: Env = Rc::new(RefCell::new(Default::default()));
+        load_file(path, &restored, &mut ctx).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let program = parser::parse("add(x, 1)").unwrap();
+        let evaluated = eval::eval_with_context(program, &restored, &mut ctx).unwrap();
+        assert_eq!(*evaluated, Object::Integer(6));
+        assert_eq!(
+            restored.borrow().get("s".into()),
+            Some(Rc::new(Object::String("say \"hi\"\n".to_string())))
+        );
+        assert_eq!(restored.borrow().get("l".into()), None);
+    }
+}
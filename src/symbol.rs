@@ -0,0 +1,135 @@
+/*!
+# Symbol
+
+Defines `Symbol`, an interned identifier. Identifiers in the AST and the
+environment are stored as symbols, so comparing or hashing them compares
+integers rather than strings.
+
+Symbols are interned in a single process-wide table. Interned names are never
+freed, which bounds the table by the number of distinct identifiers a program
+uses.
+*/
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::sync::{Mutex, OnceLock};
+
+/// An interned identifier. Two symbols are equal exactly when they were
+/// interned from equal names.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+/// A hash map keyed by symbols, using [`SymbolHasher`].
+pub type SymbolMap<V> = HashMap<Symbol, V, BuildHasherDefault<SymbolHasher>>;
+
+/// A hasher for symbols. Symbols are small sequential integers, so a single
+/// multiply spreads them well, without the cost of the default DoS-resistant
+/// hasher.
+#[derive(Default)]
+pub struct SymbolHasher(u64);
+
+impl SymbolHasher {
+    /// The multiplier of the hash, an odd constant with well-mixed bits.
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    /// Mix the given word into the hash.
+    fn add(&mut self, word: u64) {
+        self.0 = (self.0.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+    }
+}
+
+impl Hasher for SymbolHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.add(u64::from(*byte));
+        }
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.add(u64::from(n));
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The table of interned names, indexed by symbol.
+#[derive(Default)]
+struct Interner {
+    /// The symbol of each interned name.
+    symbols: HashMap<&'static str, Symbol>,
+    /// The interned names, indexed by symbol.
+    names: Vec<&'static str>,
+}
+
+/// Returns the process-wide interner.
+fn interner() -> &'static Mutex<Interner> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+impl Symbol {
+    /// Returns the symbol for the given name, interning the name if it has
+    /// not been interned before.
+    pub fn intern(name: &str) -> Symbol {
+        let mut interner = interner().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(symbol) = interner.symbols.get(name) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(interner.names.len() as u32);
+        let name: &'static str = Box::leak(name.into());
+        interner.names.push(name);
+        interner.symbols.insert(name, symbol);
+        symbol
+    }
+
+    /// Returns the symbol for the given name, if the name has been interned.
+    pub fn get(name: &str) -> Option<Symbol> {
+        let interner = interner().lock().unwrap_or_else(|e| e.into_inner());
+        interner.symbols.get(name).copied()
+    }
+
+    /// Returns the name the symbol was interned from.
+    pub fn as_str(self) -> &'static str {
+        let interner = interner().lock().unwrap_or_else(|e| e.into_inner());
+        interner.names[self.0 as usize]
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Symbol::intern(name)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Symbol({:?})", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let x = Symbol::intern("x");
+        assert_eq!(x, Symbol::intern("x"));
+        assert_eq!(x, Symbol::from("x"));
+        assert_ne!(x, Symbol::intern("y"));
+        assert_eq!(Symbol::get("x"), Some(x));
+        assert_eq!(Symbol::get("never interned"), None);
+        assert_eq!(x.as_str(), "x");
+        assert_eq!(Symbol::intern("héllo").to_string(), "héllo");
+        assert_eq!(format!("{:?}", x), r#"Symbol("x")"#);
+    }
+}
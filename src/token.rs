@@ -18,9 +18,14 @@ pub enum Token {
     /// Identifier, e.g., `add`, `foobar`, `x`, `y`, ...
     Ident(String),
     /// Integer literal, e.g., `[0-9]`
-    Int(i32),
+    Int(i64),
+    /// Integer literal that does not fit in a 64-bit signed integer, e.g.,
+    /// `99999999999999999999`
+    IntTooLarge(String),
     /// A string literal, e.g., \"Hello, world!\"
     String(String),
+    /// A string literal missing its closing quotation, e.g., \"Hello
+    UnterminatedString,
 
     /* Operators */
     /// Assignment operator `=`
@@ -35,6 +40,8 @@ pub enum Token {
     Asterisk,
     /// Division arithmetic operator `/`
     Slash,
+    /// Exponentiation arithmetic operator `**`
+    Power,
     /// Less than logical operator `<`
     Lt,
     /// Greater than logical operator `>`
@@ -51,6 +58,12 @@ pub enum Token {
     Semicolon,
     /// Colon `:`
     Colon,
+    /// Ellipsis `...`
+    Ellipsis,
+    /// Fat arrow `=>`
+    FatArrow,
+    /// Underscore `_`
+    Underscore,
 
     /// Left parenthesis `(`
     LParen,
@@ -76,12 +89,26 @@ pub enum Token {
     True,
     /// `false` keyword
     False,
+    /// `null` keyword
+    Null,
     /// `if` keyword
     If,
     /// `else` keyword
     Else,
     /// `return` keyword
     Return,
+    /// `switch` keyword
+    Switch,
+    /// `do` keyword
+    Do,
+    /// `for` keyword
+    For,
+    /// `in` keyword
+    In,
+    /// `break` keyword
+    Break,
+    /// `continue` keyword
+    Continue,
 }
 
 impl fmt::Display for Token {
@@ -91,12 +118,15 @@ impl fmt::Display for Token {
             Token::Eof => write!(f, "EOF"),
             Token::Ident(id) => write!(f, "{}", id),
             Token::Int(i) => write!(f, "{}", i),
+            Token::IntTooLarge(literal) => write!(f, "{}", literal),
+            Token::UnterminatedString => write!(f, "UNTERMINATED STRING"),
             Token::Assign => write!(f, "="),
             Token::Plus => write!(f, "+"),
             Token::Minus => write!(f, "-"),
             Token::Bang => write!(f, "!"),
             Token::Asterisk => write!(f, "*"),
             Token::Slash => write!(f, "/"),
+            Token::Power => write!(f, "**"),
             Token::Lt => write!(f, "<"),
             Token::Gt => write!(f, ">"),
             Token::Eq => write!(f, "=="),
@@ -111,32 +141,198 @@ impl fmt::Display for Token {
             Token::Let => write!(f, "LET"),
             Token::True => write!(f, "TRUE"),
             Token::False => write!(f, "FALSE"),
+            Token::Null => write!(f, "NULL"),
             Token::If => write!(f, "IF"),
             Token::Else => write!(f, "ELSE"),
             Token::Return => write!(f, "RETURN"),
+            Token::Switch => write!(f, "SWITCH"),
+            Token::Do => write!(f, "DO"),
+            Token::For => write!(f, "FOR"),
+            Token::In => write!(f, "IN"),
+            Token::Break => write!(f, "BREAK"),
+            Token::Continue => write!(f, "CONTINUE"),
             Token::String(str) => write!(f, "{}", str),
             Token::LBracket => write!(f, "["),
             Token::RBracket => write!(f, "]"),
             Token::Colon => write!(f, ":"),
+            Token::Ellipsis => write!(f, "..."),
+            Token::FatArrow => write!(f, "=>"),
+            Token::Underscore => write!(f, "_"),
         }
     }
 }
 
+/// The position of a token in the source code, as a 1-based line and column.
+/// Columns count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// The column number within the line, starting at 1.
+    pub column: usize,
+}
+
+impl Position {
+    /// Construct a new position at the given line and column.
+    pub fn new(line: usize, column: usize) -> Position {
+        Position { line, column }
+    }
+}
+
+impl Default for Position {
+    /// The position of the first character of the source code.
+    fn default() -> Self {
+        Position::new(1, 1)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Every keyword, paired with its token. A keyword can never be used as an
+/// identifier.
+pub const KEYWORDS: &[(&str, Token)] = &[
+    ("fn", Token::Function),
+    ("let", Token::Let),
+    ("true", Token::True),
+    ("false", Token::False),
+    ("null", Token::Null),
+    ("if", Token::If),
+    ("else", Token::Else),
+    ("return", Token::Return),
+    ("switch", Token::Switch),
+    ("do", Token::Do),
+    ("for", Token::For),
+    ("in", Token::In),
+    ("break", Token::Break),
+    ("continue", Token::Continue),
+];
+
 /// Return the token associated with a raw identifier. If the identifier is not
 /// associated with a defined keyword, (e.g., a user-defined identifier),
 /// defaults to `Token::Ident`.
 pub fn lookup_ident(ident: &str) -> Token {
-    match ident {
-        /* keywords */
-        "fn" => Token::Function,
-        "let" => Token::Let,
-        "true" => Token::True,
-        "false" => Token::False,
-        "if" => Token::If,
-        "else" => Token::Else,
-        "return" => Token::Return,
-
-        /* user-defined identifier */
-        _ => Token::Ident(ident.to_string()),
+    KEYWORDS
+        .iter()
+        .find(|(keyword, _)| *keyword == ident)
+        .map_or_else(
+            || Token::Ident(ident.to_string()),
+            |(_, token)| token.clone(),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_keywords() {
+        // Every keyword the parser expects maps to its token, and the table
+        // holds no others
+        let expected = [
+            ("fn", Token::Function),
+            ("let", Token::Let),
+            ("true", Token::True),
+            ("false", Token::False),
+            ("null", Token::Null),
+            ("if", Token::If),
+            ("else", Token::Else),
+            ("return", Token::Return),
+            ("switch", Token::Switch),
+            ("do", Token::Do),
+            ("for", Token::For),
+            ("in", Token::In),
+            ("break", Token::Break),
+            ("continue", Token::Continue),
+        ];
+        for (keyword, token) in &expected {
+            assert_eq!(&lookup_ident(keyword), token, "wrong token for {}", keyword);
+        }
+        assert_eq!(KEYWORDS.len(), expected.len());
+
+        // Each keyword appears in the table once
+        for (i, (keyword, _)) in KEYWORDS.iter().enumerate() {
+            assert!(
+                KEYWORDS[i + 1..].iter().all(|(other, _)| other != keyword),
+                "duplicate keyword {}",
+                keyword
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let cases = [
+            (Token::Illegal, "ILLEGAL"),
+            (Token::Eof, "EOF"),
+            (Token::Ident("foo".to_string()), "foo"),
+            (Token::Int(42), "42"),
+            (
+                Token::IntTooLarge("99999999999999999999".to_string()),
+                "99999999999999999999",
+            ),
+            (Token::String("a b".to_string()), "a b"),
+            (Token::UnterminatedString, "UNTERMINATED STRING"),
+            (Token::Assign, "="),
+            (Token::Plus, "+"),
+            (Token::Minus, "-"),
+            (Token::Bang, "!"),
+            (Token::Asterisk, "*"),
+            (Token::Slash, "/"),
+            (Token::Power, "**"),
+            (Token::Lt, "<"),
+            (Token::Gt, ">"),
+            (Token::Eq, "=="),
+            (Token::NotEq, "!="),
+            (Token::Comma, ","),
+            (Token::Semicolon, ";"),
+            (Token::Colon, ":"),
+            (Token::Ellipsis, "..."),
+            (Token::FatArrow, "=>"),
+            (Token::Underscore, "_"),
+            (Token::LParen, "("),
+            (Token::RParen, ")"),
+            (Token::LBrace, "{"),
+            (Token::RBrace, "}"),
+            (Token::LBracket, "["),
+            (Token::RBracket, "]"),
+            (Token::Function, "FUNCTION"),
+            (Token::Let, "LET"),
+            (Token::True, "TRUE"),
+            (Token::False, "FALSE"),
+            (Token::Null, "NULL"),
+            (Token::If, "IF"),
+            (Token::Else, "ELSE"),
+            (Token::Return, "RETURN"),
+            (Token::Switch, "SWITCH"),
+            (Token::Do, "DO"),
+            (Token::For, "FOR"),
+            (Token::In, "IN"),
+            (Token::Break, "BREAK"),
+            (Token::Continue, "CONTINUE"),
+        ];
+        for (token, expected) in cases {
+            assert_eq!(token.to_string(), expected, "wrong display for {:?}", token);
+        }
+    }
+
+    #[test]
+    fn test_lookup_identifiers() {
+        // Any other word is an identifier, including those that only resemble
+        // a keyword
+        for ident in [
+            "x", "foo", "func", "True", "FALSE", "nul", "iff", "lets", "returned", "fn_", "_if",
+            "in_",
+        ] {
+            assert_eq!(
+                lookup_ident(ident),
+                Token::Ident(ident.to_string()),
+                "wrong token for {}",
+                ident
+            );
+        }
     }
 }
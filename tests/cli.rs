@@ -0,0 +1,264 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// Write a Monkey source file with the given contents to a unique temporary
+/// path, returning the path.
+fn write_source(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "monkey-rs-cli-{}-{}.monkey",
+        name,
+        std::process::id()
+    ));
+    fs::write(&path, contents).expect("failed to write source file");
+    path
+}
+
+/// Run the `monkey` binary with the given arguments.
+fn run_monkey(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_monkey"))
+        .args(args)
+        .output()
+        .expect("failed to run monkey")
+}
+
+/// Run the `monkey` binary with no arguments, piping the given input through
+/// STDIN.
+fn run_monkey_with_stdin(input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_monkey"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run monkey");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .expect("failed to write to stdin");
+    child.wait_with_output().expect("failed to run monkey")
+}
+
+#[test]
+fn test_tokens_flag() {
+    let path = write_source("tokens", "let x = 1;\nx");
+    let output = run_monkey(&["--tokens", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "1:1 Let\n1:5 Ident(\"x\")\n1:7 Assign\n1:9 Int(1)\n1:10 Semicolon\n2:1 Ident(\"x\")\n2:2 Eof\n"
+    );
+}
+
+#[test]
+fn test_ast_flag() {
+    let path = write_source("ast", "let f = fn(x) { if (x > 1) { x * (2 + 3) } };");
+    let output = run_monkey(&["--ast", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "let f = fn(x) {\n  if (x > 1) {\n    x * (2 + 3);\n  };\n};\n"
+    );
+}
+
+#[test]
+fn test_ast_flag_parse_error() {
+    let path = write_source("ast-error", "let = 5;");
+    let output = run_monkey(&["--ast", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Expected identifier after 'let'"));
+}
+
+#[test]
+fn test_check_flag() {
+    // Only the syntax is checked, so neither output nor runtime errors occur
+    let path = write_source("check", "puts(1);\nlet x = 1 + true;");
+    let output = run_monkey(&["--check", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_check_flag_parse_error() {
+    let valid = write_source("check-valid", "let x = 1;");
+    let invalid = write_source("check-invalid", "let = 5;");
+    let output = run_monkey(&[
+        "--check",
+        invalid.to_str().unwrap(),
+        valid.to_str().unwrap(),
+    ]);
+    fs::remove_file(&valid).unwrap();
+    fs::remove_file(&invalid).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with(invalid.to_str().unwrap()));
+    assert!(stderr.contains("Expected identifier after 'let'"));
+    assert!(!stderr.contains(valid.to_str().unwrap()));
+}
+
+#[test]
+fn test_parse_error_diagnostic() {
+    let output = run_monkey_with_stdin("let x = 1;\nlet = 5;");
+
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "<stdin>: parse error: Encountered 1 error(s) while parsing:\n\
+         Expected identifier after 'let'\n \
+         --> 2:5\n  \
+         |\n\
+         2 | let = 5;\n  \
+         |     ^\n"
+    );
+}
+
+#[test]
+fn test_stdin_program() {
+    let output = run_monkey_with_stdin("puts(1 + 1); let x = 5; x * 2");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2\n10\n");
+}
+
+#[test]
+fn test_empty_stdin() {
+    let output = run_monkey_with_stdin("");
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_multiple_files_share_environment() {
+    let lib = write_source("multi-lib", "let add = fn(a, b) { a + b };");
+    let main = write_source("multi-main", "add(2, 3)");
+    let output = run_monkey(&[lib.to_str().unwrap(), main.to_str().unwrap()]);
+    fs::remove_file(&lib).unwrap();
+    fs::remove_file(&main).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "5\n");
+}
+
+#[test]
+fn test_multiple_files_report_failing_file() {
+    let first = write_source("multi-first", "let x = 1;");
+    let second = write_source("multi-second", "x + y");
+    let third = write_source("multi-third", "puts(x)");
+    let output = run_monkey(&[
+        first.to_str().unwrap(),
+        second.to_str().unwrap(),
+        third.to_str().unwrap(),
+    ]);
+    for path in [&first, &second, &third] {
+        fs::remove_file(path).unwrap();
+    }
+
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        format!(
+            "{}: evaluation error at line 1:5: identifier not found: y\n",
+            second.display()
+        )
+    );
+}
+
+#[test]
+fn test_import_relative_to_importing_file() {
+    let lib = write_source("import-lib", "let add = fn(a, b) { a + b };");
+    let main = write_source(
+        "import-main",
+        &format!(
+            r#"import("{}"); add(2, 3)"#,
+            lib.file_name().unwrap().to_str().unwrap()
+        ),
+    );
+    let output = run_monkey(&[main.to_str().unwrap()]);
+    fs::remove_file(&lib).unwrap();
+    fs::remove_file(&main).unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "5\n");
+}
+
+#[test]
+fn test_puts_only_program_has_no_trailing_null() {
+    let path = write_source("puts-only", r#"puts("hello"); puts(1 + 1);"#);
+    let output = run_monkey(&[path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n2\n");
+}
+
+#[test]
+fn test_program_arguments() {
+    let path = write_source("args", "puts(args())");
+    let output = run_monkey(&[path.to_str().unwrap(), "foo", "bar"]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "[foo, bar]\n");
+
+    // Arguments follow the source files, ending at an explicit `--`
+    let lib = write_source("args-lib", "let n = len(args());");
+    let main = write_source("args-main", "puts(n, args())");
+    let output = run_monkey(&[
+        lib.to_str().unwrap(),
+        main.to_str().unwrap(),
+        "--",
+        "-v",
+        "x.txt",
+    ]);
+    fs::remove_file(&lib).unwrap();
+    fs::remove_file(&main).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2\n[-v, x.txt]\n");
+}
+
+#[test]
+fn test_hyphenated_program_arguments() {
+    // Arguments that look like options are passed to the program, as are
+    // options following the source files
+    let path = write_source("args-hyphen", "puts(args())");
+    let output = run_monkey(&[path.to_str().unwrap(), "-v", "--n=3", "--ast"]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "[-v, --n=3, --ast]\n"
+    );
+}
+
+#[test]
+fn test_program_arguments_after_separator() {
+    // Every argument after `--` is passed to the program, even `.monkey` files
+    let path = write_source("args-separator", "puts(args())");
+    let output = run_monkey(&[path.to_str().unwrap(), "--", "other.monkey", "--"]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "[other.monkey, --]\n"
+    );
+}
@@ -0,0 +1,128 @@
+use monkey_rs::{
+    eval::{context::Context, error::ErrorKind, object::Object},
+    Error, Interpreter,
+};
+
+#[test]
+fn test_environment_persists_across_evaluations() {
+    let mut interpreter = Interpreter::new();
+
+    interpreter
+        .eval_str("let x = 1;")
+        .expect("evaluation failed");
+    let result = interpreter.eval_str("x + 1").expect("evaluation failed");
+
+    assert_eq!(*result, Object::Integer(2));
+}
+
+#[test]
+fn test_reset_discards_bindings() {
+    let mut interpreter = Interpreter::new();
+
+    interpreter
+        .eval_str("let x = 1;")
+        .expect("evaluation failed");
+    interpreter.reset();
+
+    match interpreter.eval_str("x") {
+        Err(Error::Eval(e)) => assert_eq!(e.to_string(), "identifier not found: x"),
+        other => panic!("expected evaluation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_error_display_prefixes_stage() {
+    let mut interpreter = Interpreter::new();
+
+    let parse_err = interpreter.eval_str("let = 5;").unwrap_err();
+    assert!(matches!(parse_err, Error::Parse(_)));
+    assert!(parse_err.to_string().starts_with("parse error: "));
+
+    let eval_err = interpreter.eval_str("5 + true").unwrap_err();
+    assert_eq!(
+        eval_err.to_string(),
+        "evaluation error at line 1:3: unknown operator: 5 + true"
+    );
+}
+
+#[test]
+fn test_error_display_includes_position() {
+    let mut interpreter = Interpreter::new();
+
+    let err = interpreter
+        .eval_str("let x = 1;\nlet y = 2;\nx + zed")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "evaluation error at line 3:5: identifier not found: zed"
+    );
+
+    // Errors raised within a function body point into the body, not the call
+    let err = interpreter
+        .eval_str("let f = fn(n) {\n  n / 0\n};\nf(1)")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "evaluation error at line 2:5: division by zero"
+    );
+}
+
+#[test]
+fn test_import_cycle_is_an_error() {
+    let dir = std::env::temp_dir();
+    let a = dir.join(format!("monkey-rs-cycle-a-{}.monkey", std::process::id()));
+    let b = dir.join(format!("monkey-rs-cycle-b-{}.monkey", std::process::id()));
+    let a_src = format!(r#"import("{}");"#, b.file_name().unwrap().to_str().unwrap());
+    let b_src = format!(r#"import("{}");"#, a.file_name().unwrap().to_str().unwrap());
+    std::fs::write(&a, &a_src).unwrap();
+    std::fs::write(&b, &b_src).unwrap();
+
+    let result = Interpreter::new().eval_source(&a_src, &a);
+    std::fs::remove_file(&a).unwrap();
+    std::fs::remove_file(&b).unwrap();
+
+    match result {
+        Err(Error::Eval(e)) => assert_eq!(e.kind(), ErrorKind::Import),
+        other => panic!("expected import error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_step_limit_bounds_each_evaluation() {
+    let mut interpreter = Interpreter::with_context(Context::default().with_step_limit(1000));
+
+    interpreter
+        .eval_str("let count = fn(n) { if (n == 0) { 0 } else { count(n - 1) } };")
+        .expect("evaluation failed");
+
+    // Each evaluation gets the full budget, so repeated evaluations that fit
+    // within the limit keep succeeding
+    for _ in 0..3 {
+        let result = interpreter
+            .eval_str("count(10)")
+            .expect("evaluation failed");
+        assert_eq!(*result, Object::Integer(0));
+    }
+
+    match interpreter.eval_str("count(10000)") {
+        Err(Error::Eval(e)) => assert_eq!(e.kind(), ErrorKind::StepLimit),
+        other => panic!("expected step limit error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_eval_expr() {
+    assert_eq!(
+        *monkey_rs::eval_expr("2 * 21").unwrap(),
+        Object::Integer(42)
+    );
+
+    match monkey_rs::eval_expr("foo") {
+        Err(Error::Eval(e)) => assert_eq!(e.kind(), ErrorKind::IdentifierNotFound),
+        other => panic!("expected evaluation error, got {:?}", other),
+    }
+    assert!(matches!(
+        monkey_rs::eval_expr("let = 5;"),
+        Err(Error::Parse(_))
+    ));
+}
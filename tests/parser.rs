@@ -0,0 +1,43 @@
+use monkey_rs::parser::{self, ast};
+use monkey_rs::token::Position;
+
+#[test]
+fn test_match_parsed_program() {
+    let node = parser::parse("let x = 5; x + 1").expect("failed to parse input");
+
+    let statements = match node {
+        ast::Node::Program(statements) => statements,
+        other => panic!("expected Node::Program, got {:?}", other),
+    };
+
+    assert_eq!(statements.len(), 2);
+    assert_eq!(
+        statements[0],
+        ast::Statement::Let("x".into(), ast::Expression::Lit(ast::Literal::Integer(5)))
+    );
+    match &statements[1] {
+        ast::Statement::Expr(ast::Expression::Infix(_, left, right, position)) => {
+            assert_eq!(
+                **left,
+                ast::Expression::Identifier("x".into(), Position::new(1, 12))
+            );
+            assert_eq!(*position, Position::new(1, 14));
+            assert_eq!(**right, ast::Expression::Lit(ast::Literal::Integer(1)));
+        }
+        other => panic!("expected infix expression statement, got {:?}", other),
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_precedence_alias() {
+    use monkey_rs::{
+        parser::precedence::{self, Precdence, Precedence},
+        token::Token,
+    };
+
+    // The misspelled name still refers to the same type
+    let precedence: Precdence = precedence::token_precedence(&Token::Asterisk);
+    assert_eq!(precedence, Precedence::Product);
+    assert!(Precedence::Sum < Precedence::Product);
+}